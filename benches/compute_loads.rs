@@ -0,0 +1,137 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use proton_vpn_binary_status::test_utils::synthetic::{
+    generate_logicals, generate_status_file,
+};
+use proton_vpn_binary_status::{
+    compute_loads_with_jitter, compute_travel_distance, explain_penalty,
+    jitter_generator, CountryCode, Load, Location, Parser, ServerStatus,
+    STATUS_ENABLED,
+};
+use std::hint::black_box;
+
+const SEED: u64 = 42;
+const SERVER_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn user_location() -> Location {
+    Location {
+        latitude: 46.2044, // Geneva
+        longitude: 6.1432,
+    }
+}
+
+fn bench_compute_loads(c: &mut Criterion) {
+    let user_country = Some(CountryCode::try_from(b"CH").unwrap());
+
+    let mut group = c.benchmark_group("compute_loads");
+    group.sample_size(20);
+
+    for count in SERVER_COUNTS {
+        let logicals = generate_logicals(count, SEED);
+        let status_file = generate_status_file(count, SEED);
+        let mut loads = vec![Load::default(); count];
+
+        for (name, user_location) in
+            [("location", Some(user_location())), ("no_location", None)]
+        {
+            let mut jitter = jitter_generator();
+            group.bench_function(
+                BenchmarkId::new(format!("jitter/{name}"), count),
+                |b| {
+                    b.iter(|| {
+                        compute_loads_with_jitter(
+                            &mut loads,
+                            &logicals,
+                            &status_file,
+                            &user_location,
+                            &user_country,
+                            &mut jitter,
+                        )
+                    })
+                },
+            );
+
+            group.bench_function(
+                BenchmarkId::new(format!("no_jitter/{name}"), count),
+                |b| {
+                    b.iter(|| {
+                        compute_loads_with_jitter(
+                            &mut loads,
+                            &logicals,
+                            &status_file,
+                            &user_location,
+                            &user_country,
+                            &mut || 0.0,
+                        )
+                    })
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+// Measures the parts of the scoring loop in isolation, over the servers of a
+// 10k server list.
+fn bench_scoring_parts(c: &mut Criterion) {
+    let logicals = generate_logicals(10_000, SEED);
+    let user_location = user_location();
+    let user_country = Some(CountryCode::try_from(b"CH").unwrap());
+    let status = ServerStatus::default();
+
+    c.bench_function("travel_distance/10000", |b| {
+        b.iter(|| {
+            for logical in &logicals {
+                black_box(compute_travel_distance(
+                    &logical.exit_location,
+                    &logical.entry_location,
+                    &user_location,
+                ));
+            }
+        })
+    });
+
+    c.bench_function("penalty/10000", |b| {
+        b.iter(|| {
+            for logical in &logicals {
+                black_box(explain_penalty(logical, &status, &user_country));
+            }
+        })
+    });
+}
+
+// Measures counting the enabled servers of a 100k server status file, with
+// and without decoding the whole records.
+fn bench_count_enabled(c: &mut Criterion) {
+    let status_file = generate_status_file(100_000, SEED);
+    let parser = Parser::try_from(&status_file[..]).unwrap();
+
+    c.bench_function("count_enabled/iter/100000", |b| {
+        b.iter(|| {
+            parser
+                .iter()
+                .filter(|server| server.status & STATUS_ENABLED != 0)
+                .count()
+        })
+    });
+
+    c.bench_function("count_enabled/iter_status_bytes/100000", |b| {
+        b.iter(|| {
+            parser
+                .iter_status_bytes()
+                .filter(|status| status & STATUS_ENABLED != 0)
+                .count()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_compute_loads,
+    bench_scoring_parts,
+    bench_count_enabled
+);
+criterion_main!(benches);
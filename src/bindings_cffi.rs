@@ -6,7 +6,116 @@ use std::ffi::{c_char, c_int, CString};
 use std::ptr::null_mut;
 use std::slice;
 
-use super::{compute_loads, CountryCode, Load, Location, Logical};
+use super::{
+    compute_loads, CountryCode, Load, Location, Logical, Parser, Result,
+    StatusReference,
+};
+
+/// The load of a server as written by `compute_loads_cffi`, with the layout
+/// `Load` had in version 1 of the structs shared with C.
+///
+/// The layout is frozen so that the existing C callers, which allocate the
+/// loads with this size, keep working. `compute_loads_cffi_v3` writes the
+/// current `Load`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[repr(C)]
+pub struct LoadV1 {
+    pub is_enabled: bool,
+    pub is_visible: bool,
+    pub is_autoconnectable: bool,
+    pub load: u8,
+    pub score: f64,
+    #[cfg(feature = "debug")]
+    pub debug_partial_score: f64,
+}
+
+impl From<&Load> for LoadV1 {
+    fn from(load: &Load) -> Self {
+        Self {
+            is_enabled: load.is_enabled,
+            is_visible: load.is_visible,
+            is_autoconnectable: load.is_autoconnectable,
+            load: load.load,
+            score: load.score.value(),
+            #[cfg(feature = "debug")]
+            debug_partial_score: load.debug.partial_score,
+        }
+    }
+}
+
+// The `repr(C)` layout of the structs shared with C, checked at compile time
+// so that a field change (e.g. enabling the "debug" feature) doesn't silently
+// break the C headers. The offsets assume the usual C alignment rules: f32
+// and u32 are 4-byte aligned, f64 is 8-byte aligned and bool is one byte.
+//
+// The byte order of the numbers is the native one, use `Load::to_wire_le`
+// when the loads leave the process.
+const _: () = {
+    use std::mem::{offset_of, size_of};
+
+    // u32 index, 4 bytes padding, f64 penalty, u8 cost, 7 bytes padding.
+    assert!(offset_of!(StatusReference, index) == 0);
+    assert!(offset_of!(StatusReference, penalty) == 8);
+    assert!(offset_of!(StatusReference, cost) == 16);
+    assert!(size_of::<StatusReference>() == 24);
+
+    assert!(offset_of!(Location, latitude) == 0);
+    assert!(offset_of!(Location, longitude) == 4);
+    assert!(size_of::<Location>() == 8);
+
+    // The 2 byte country codes are followed by 4 bytes of padding. The entry
+    // country is zeroed when there is none.
+    assert!(offset_of!(Logical, status_reference) == 0);
+    assert!(offset_of!(Logical, entry_location) == 24);
+    assert!(offset_of!(Logical, exit_location) == 32);
+    assert!(offset_of!(Logical, exit_country) == 40);
+    assert!(offset_of!(Logical, entry_country) == 42);
+    assert!(size_of::<Logical>() == 48);
+
+    // 3 bool flags and the u8 load, 4 bytes padding, then 8-byte f64s.
+    assert!(offset_of!(Load, is_enabled) == 0);
+    assert!(offset_of!(Load, is_visible) == 1);
+    assert!(offset_of!(Load, is_autoconnectable) == 2);
+    assert!(offset_of!(Load, load) == 3);
+    assert!(offset_of!(Load, status) == 4);
+    assert!(offset_of!(Load, score) == 8);
+    assert!(offset_of!(Load, distance_km) == 16);
+    assert!(offset_of!(Load, display_fraction) == 24);
+    assert!(offset_of!(Load, penalty) == 32);
+    #[cfg(not(feature = "debug"))]
+    assert!(size_of::<Load>() == 40);
+    #[cfg(feature = "debug")]
+    {
+        // The debug fields are 3 f64s.
+        assert!(offset_of!(Load, debug) == 40);
+        assert!(size_of::<Load>() == 64);
+    }
+
+    // The version 1 layout, which must never change.
+    assert!(offset_of!(LoadV1, is_enabled) == 0);
+    assert!(offset_of!(LoadV1, is_visible) == 1);
+    assert!(offset_of!(LoadV1, is_autoconnectable) == 2);
+    assert!(offset_of!(LoadV1, load) == 3);
+    assert!(offset_of!(LoadV1, score) == 8);
+    #[cfg(not(feature = "debug"))]
+    assert!(size_of::<LoadV1>() == 16);
+    #[cfg(feature = "debug")]
+    {
+        assert!(offset_of!(LoadV1, debug_partial_score) == 16);
+        assert!(size_of::<LoadV1>() == 24);
+    }
+};
+
+/// The version of the layout of the structs shared with C. It is bumped on
+/// every layout change, so that the C callers can check that their headers
+/// match the library.
+///
+/// Version 2 added the entry country of `Logical`, in what was padding, and
+/// version 3 the penalty of `Load`, after its display fraction. The loads
+/// of the other versions are not compatible, so `compute_loads_cffi` keeps
+/// writing the version 1 `LoadV1`, and `compute_loads_cffi_v3` writes the
+/// version 3 `Load`.
+pub const FFI_STRUCT_VERSION: u32 = 3;
 
 fn set_err(out_error: *mut *mut c_char, msg: &str) {
     if out_error.is_null() {
@@ -22,9 +131,55 @@ fn set_err(out_error: *mut *mut c_char, msg: &str) {
     }
 }
 
+// The pointers are provided by the C caller, which is responsible for their
+// validity. The loads are written with the version 1 layout, `LoadV1`.
 // nosem: rust.lang.security.unsafe-usage.unsafe-usage
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn compute_loads_cffi(
+    logicals_ptr: *const Logical,
+    logicals_len: usize,
+    status_file_ptr: *const u8,
+    status_file_len: usize,
+    user_location_ptr: *const Location,
+    user_country_ptr: *const [u8; 2],
+    loads: *mut LoadV1,
+    error: *mut *mut c_char,
+) -> c_int {
+    compute_loads_c(
+        logicals_ptr,
+        logicals_len,
+        status_file_ptr,
+        status_file_len,
+        user_location_ptr,
+        user_country_ptr,
+        loads,
+        error,
+        |output_slice, logicals, status_file, user_location, user_country| {
+            let mut computed = vec![Load::default(); output_slice.len()];
+            compute_loads(
+                &mut computed,
+                logicals,
+                status_file,
+                user_location,
+                user_country,
+            )?;
+
+            for (output, load) in std::iter::zip(output_slice, &computed) {
+                *output = LoadV1::from(load);
+            }
+
+            Ok(())
+        },
+    )
+}
+
+// Same as `compute_loads_cffi`, but writes the loads with the current
+// layout, `Load`, whose version is `FFI_STRUCT_VERSION`.
+// nosem: rust.lang.security.unsafe-usage.unsafe-usage
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn compute_loads_cffi_v3(
     logicals_ptr: *const Logical,
     logicals_len: usize,
     status_file_ptr: *const u8,
@@ -33,6 +188,47 @@ pub extern "C" fn compute_loads_cffi(
     user_country_ptr: *const [u8; 2],
     loads: *mut Load,
     error: *mut *mut c_char,
+) -> c_int {
+    compute_loads_c(
+        logicals_ptr,
+        logicals_len,
+        status_file_ptr,
+        status_file_len,
+        user_location_ptr,
+        user_country_ptr,
+        loads,
+        error,
+        |output_slice, logicals, status_file, user_location, user_country| {
+            compute_loads(
+                output_slice,
+                logicals,
+                status_file,
+                user_location,
+                user_country,
+            )
+        },
+    )
+}
+
+// Reads the arguments of the C entry points, then computes the loads into
+// the C caller's `loads` with `compute`.
+#[allow(clippy::too_many_arguments)]
+fn compute_loads_c<T>(
+    logicals_ptr: *const Logical,
+    logicals_len: usize,
+    status_file_ptr: *const u8,
+    status_file_len: usize,
+    user_location_ptr: *const Location,
+    user_country_ptr: *const [u8; 2],
+    loads: *mut T,
+    error: *mut *mut c_char,
+    compute: impl FnOnce(
+        &mut [T],
+        &[Logical],
+        &[u8],
+        &Option<Location>,
+        &Option<CountryCode>,
+    ) -> Result<()>,
 ) -> c_int {
     if !error.is_null() {
         // nosem: rust.lang.security.unsafe-usage.unsafe-usage
@@ -81,9 +277,9 @@ pub extern "C" fn compute_loads_cffi(
         // nosem: rust.lang.security.unsafe-usage.unsafe-usage
         unsafe { slice::from_raw_parts_mut(loads, logicals_len) };
 
-    if let Err(e) = compute_loads(
+    if let Err(e) = compute(
         output_slice,
-        &logicals,
+        logicals,
         status_file,
         &user_location,
         &user_country,
@@ -95,8 +291,54 @@ pub extern "C" fn compute_loads_cffi(
     0
 }
 
+// Returns the number of server records in the status file, so that the C
+// callers can check it against their logicals before scoring. Returns -1 for
+// a null pointer and -3 if the status file can't be parsed, as
+// `compute_loads_cffi` does.
+// nosem: rust.lang.security.unsafe-usage.unsafe-usage
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn status_file_record_count(
+    status_file_ptr: *const u8,
+    status_file_len: usize,
+    error: *mut *mut c_char,
+) -> i64 {
+    if !error.is_null() {
+        // nosem: rust.lang.security.unsafe-usage.unsafe-usage
+        unsafe {
+            *error = null_mut();
+        }
+    }
+
+    if status_file_ptr.is_null() {
+        set_err(error, "null pointer for required parameter");
+        return -1;
+    }
+
+    let status_file =
+        // nosem: rust.lang.security.unsafe-usage.unsafe-usage
+        unsafe { slice::from_raw_parts(status_file_ptr, status_file_len) };
+
+    match Parser::try_from(status_file) {
+        // A record is several bytes long, so the count always fits.
+        Ok(statuses) => i64::try_from(statuses.len()).unwrap_or(i64::MAX),
+        Err(e) => {
+            set_err(error, &e.to_string());
+            -3
+        }
+    }
+}
+
+// Returns `FFI_STRUCT_VERSION`, the version of the layout of the structs
+// the library was built with.
+#[no_mangle]
+pub extern "C" fn ffi_struct_version() -> u32 {
+    FFI_STRUCT_VERSION
+}
+
 // nosem: rust.lang.security.unsafe-usage.unsafe-usage
 #[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn free_c_string(s: *mut c_char) {
     if s.is_null() {
         return;
@@ -2,7 +2,10 @@
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
 
-use super::{compute_loads, CountryCode, Load, Location, Logical, Result};
+use super::{
+    compute_loads, jitter_enabled, legacy_enabled, score_within_jitter,
+    CountryCode, Load, Location, Logical, Result,
+};
 
 #[uniffi::export]
 pub fn compute_loads_uniffi(
@@ -21,3 +24,21 @@ pub fn compute_loads_uniffi(
     )?;
     Ok(result_loads)
 }
+
+// Whether the library was built with the `jitter` feature.
+#[uniffi::export]
+pub fn jitter_enabled_uniffi() -> bool {
+    jitter_enabled()
+}
+
+// Whether the library was built with the `legacy` feature.
+#[uniffi::export]
+pub fn legacy_enabled_uniffi() -> bool {
+    legacy_enabled()
+}
+
+// Whether two scores differ by less than the jitter band.
+#[uniffi::export]
+pub fn score_within_jitter_uniffi(a: f64, b: f64) -> bool {
+    score_within_jitter(a, b)
+}
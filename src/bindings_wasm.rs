@@ -0,0 +1,91 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use wasm_bindgen::prelude::*;
+
+use super::json;
+use super::{Location, Logical};
+
+/// Computes the loads of the logicals and returns them as a JSON array of
+/// `Load`.
+///
+/// `logicals_json` is a JSON array of `Logical`, in the same format as the
+/// serde representation. The user location is only used when both
+/// coordinates are given. Errors are thrown as JavaScript errors.
+#[wasm_bindgen]
+pub fn compute_loads_wasm(
+    logicals_json: &str,
+    status_file: &[u8],
+    user_latitude: Option<f32>,
+    user_longitude: Option<f32>,
+    user_country: Option<String>,
+) -> Result<String, JsError> {
+    let user_location =
+        user_latitude
+            .zip(user_longitude)
+            .map(|(latitude, longitude)| Location {
+                latitude,
+                longitude,
+            });
+
+    compute_loads_json(
+        logicals_json,
+        status_file,
+        &user_location,
+        user_country.as_deref(),
+    )
+    .map_err(|error| JsError::new(&error))
+}
+
+// Does the work of `compute_loads_wasm`, without depending on JavaScript.
+fn compute_loads_json(
+    logicals_json: &str,
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: Option<&str>,
+) -> Result<String, String> {
+    let logicals = serde_json::from_str::<Vec<Logical>>(logicals_json)
+        .map_err(|error| format!("Invalid logicals: {error}"))?;
+
+    json::compute_loads_json(
+        &logicals,
+        status_file,
+        user_location,
+        user_country,
+    )
+    .map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Load;
+
+    type AnyResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_compute_loads_json() -> AnyResult {
+        let logicals = serde_json::to_string(&[Logical::default()])?;
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 42_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 0
+        ];
+
+        let loads =
+            compute_loads_json(&logicals, &status_file, &None, Some("ch"))?;
+        let loads = serde_json::from_str::<Vec<Load>>(&loads)?;
+        assert_eq!(loads.len(), 1);
+        assert_eq!(loads[0].load, 42);
+
+        assert!(compute_loads_json("{}", &status_file, &None, None).is_err());
+        assert!(compute_loads_json(
+            &logicals,
+            &status_file,
+            &None,
+            Some("CHE")
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use super::error::*;
+use super::status::Parser;
+use super::Logical;
+
+/// A response bundling the logicals and the status file.
+///
+/// The blob starts with the length of the logicals section as a little
+/// endian dword, followed by the logicals as a JSON array of `Logical`, and
+/// then by the status file.
+pub struct CombinedBlob;
+
+impl CombinedBlob {
+    /// Parses and validates both sections of the blob.
+    pub fn parse(bytes: &[u8]) -> Result<(Vec<Logical>, Parser<'_>)> {
+        let (length, rest) =
+            bytes.split_first_chunk::<4>().ok_or_else(|| {
+                Error::ParserError(
+                    "Combined blob is too short for its header".to_string(),
+                )
+            })?;
+        let length = u32::from_le_bytes(*length) as usize;
+
+        if length > rest.len() {
+            return Err(Error::ParserError(format!(
+                "Combined blob logicals section of {length} bytes overflows the {} remaining bytes",
+                rest.len()
+            )));
+        }
+        let (logicals, status_file) = rest.split_at(length);
+
+        let logicals = serde_json::from_slice::<Vec<Logical>>(logicals)
+            .map_err(|error| Error::JsonError(error.to_string()))?;
+        let statuses = Parser::try_from(status_file)?;
+
+        Ok((logicals, statuses))
+    }
+
+    /// Same as `parse`, but also checks that there is one status record per
+    /// logical, for the responses in which the back end aligns them.
+    pub fn parse_aligned(bytes: &[u8]) -> Result<(Vec<Logical>, Parser<'_>)> {
+        let (logicals, statuses) = Self::parse(bytes)?;
+
+        if logicals.len() != statuses.len() {
+            return Err(Error::ParserError(format!(
+                "Combined blob has {} logicals but {} status records",
+                logicals.len(),
+                statuses.len()
+            )));
+        }
+
+        Ok((logicals, statuses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    fn combined_blob(logicals: &[u8], status_file: &[u8]) -> Vec<u8> {
+        let mut blob = (logicals.len() as u32).to_le_bytes().to_vec();
+        blob.extend_from_slice(logicals);
+        blob.extend_from_slice(status_file);
+        blob
+    }
+
+    #[test_log::test]
+    fn test_parse() -> AnyResult {
+        let logicals = serde_json::to_vec(&[Logical::default()])?;
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 42_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 0
+        ];
+
+        let blob = combined_blob(&logicals, &status_file);
+        let (parsed_logicals, statuses) = CombinedBlob::parse_aligned(&blob)?;
+        assert_eq!(parsed_logicals, [Logical::default()]);
+        assert_eq!(statuses.iter().next().map(|status| status.load), Some(42));
+
+        // One logical too many
+        let logicals = serde_json::to_vec(&vec![Logical::default(); 2])?;
+        let blob = combined_blob(&logicals, &status_file);
+        assert_eq!(CombinedBlob::parse(&blob)?.0.len(), 2);
+        assert!(matches!(
+            CombinedBlob::parse_aligned(&blob),
+            Err(Error::ParserError(_))
+        ));
+
+        // Invalid sections
+        assert!(matches!(
+            CombinedBlob::parse(&combined_blob(b"{}", &status_file)),
+            Err(Error::JsonError(_))
+        ));
+        assert!(matches!(
+            CombinedBlob::parse(&combined_blob(&logicals, &status_file[..9])),
+            Err(Error::ParserError(_))
+        ));
+
+        // Invalid framing
+        assert!(CombinedBlob::parse(&[1_u8, 0_u8]).is_err());
+        let mut blob = combined_blob(&logicals, &status_file);
+        blob[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            CombinedBlob::parse(&blob),
+            Err(Error::ParserError(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -2,12 +2,29 @@
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
 use super::compute_score::{
-    compute_score, ComputeScoreServerParams, STATUS_AUTOCONNECTABLE,
-    STATUS_ENABLED, STATUS_VISIBLE,
+    compute_base_score, compute_penalty, compute_user_travel_distance_legs,
+    ComputeScoreServerParams, ScoreBreakdown, OVER_CAPACITY_PENALTY,
+    STATUS_AUTOCONNECTABLE, STATUS_ENABLED, STATUS_VISIBLE,
 };
+use super::country_stats::country_load_stats;
 use super::jitter;
-use super::status::Parser;
-use super::{CountryCode, Error, Load, Location, Logical, Result};
+#[cfg(feature = "debug")]
+use super::load::LoadDebugFields;
+use super::status::{
+    validate_server, DefaultedServer, ParseErrorCollector, Parser, ServerStatus,
+};
+use super::validate::check_duplicate_status_indices;
+use super::{
+    CountryCode, DistanceMode, Error, Load, Location, Logical, Result, Score,
+    ScoringConfig, ServerIndex,
+};
+use std::collections::{HashMap, HashSet};
+
+// The reason reported for servers whose status index is past the end of the
+// status file.
+const STATUS_INDEX_OUT_OF_RANGE: &str = "Status index is out of range";
+const INVALID_SERVER_LOCATION: &str =
+    "Server location is not a valid coordinate";
 
 /// Computes the load for each server based on the user location and status file.
 /// The function updates the `loads` slice with the computed values.
@@ -24,101 +41,1364 @@ pub fn compute_loads(
     user_location: &Option<Location>,
     user_country: &Option<CountryCode>,
 ) -> Result<()> {
+    compute_loads_with_config(
+        loads,
+        logicals,
+        status_file,
+        user_location,
+        user_country,
+        &ScoringConfig::default(),
+    )
+}
+
+/// Same as `compute_loads`, but scores the servers with the given
+/// `ScoringConfig` instead of the default one.
+///
+/// Returns an error if the configuration is invalid, see
+/// `ScoringConfig::validate`.
+pub fn compute_loads_with_config(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+) -> Result<()> {
+    let mut normalized_jitter = {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("jitter").entered();
+
+        jitter::generator_with(&config.jitter)?
+    };
+
+    compute_loads_impl(
+        loads,
+        logicals,
+        status_file,
+        user_location,
+        user_country,
+        config,
+        &mut normalized_jitter,
+    )
+}
+
+/// Same as `compute_loads`, but draws the jitter of each server from the
+/// given generator instead of a freshly seeded one.
+///
+/// Keeping the generator alive across calls continues the same random
+/// stream between refreshes, and a deterministic generator (e.g. `|| 0.0`)
+/// makes the scores reproducible. The generator must return normalized
+/// jitter values, such as the ones returned by `jitter_generator`.
+pub fn compute_loads_with_jitter(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    normalized_jitter: &mut impl FnMut() -> f64,
+) -> Result<()> {
+    compute_loads_impl(
+        loads,
+        logicals,
+        status_file,
+        user_location,
+        user_country,
+        &ScoringConfig::default(),
+        normalized_jitter,
+    )
+}
+
+/// Same as `compute_loads`, but first checks that no two logicals reference
+/// the same status index.
+///
+/// Returns `Error::DuplicateStatusIndex` for the first duplicate found, such
+/// logicals would otherwise silently be scored against the same record.
+pub fn compute_loads_checked(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+) -> Result<()> {
+    check_duplicate_status_indices(logicals)?;
+
+    compute_loads(loads, logicals, status_file, user_location, user_country)
+}
+
+/// Same as `compute_loads`, but deprioritizes the servers of over-subscribed
+/// countries.
+///
+/// `caps` maps a country to the maximum mean load (in percent, like
+/// `Load::load`) of its servers. When the mean load of the servers of a
+/// country exceeds its cap, all its servers get an extra penalty. Countries
+/// without a cap are not affected.
+pub fn compute_loads_with_country_caps(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    caps: &HashMap<CountryCode, f64>,
+) -> Result<()> {
+    let config = ScoringConfig::default();
+    let user_location =
+        resolve_user_location(user_location, user_country, &config)?;
+
+    // The cap applies to the mean load of the servers of the country, so it
+    // must be known before any of them is scored.
+    let over_capacity: HashSet<CountryCode> = if caps.is_empty() {
+        HashSet::new()
+    } else {
+        country_load_stats(logicals, status_file)?
+            .into_iter()
+            .filter(|(country, stat)| {
+                caps.get(country).is_some_and(|&cap| stat.mean_load > cap)
+            })
+            .map(|(country, _)| country)
+            .collect()
+    };
+
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        &config,
+        &ServerStatus::default(),
+        &mut jitter::generator(),
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, logical| ServerExtras {
+            penalty: if over_capacity.contains(&logical.exit_country) {
+                OVER_CAPACITY_PENALTY
+            } else {
+                0.0
+            },
+            ..Default::default()
+        },
+        user_travel_distance_legs(&user_location, config.distance_mode),
+    )
+}
+
+/// Same as `compute_loads`, but scores the logicals at the indices of
+/// `overrides` as if their exit country was the overriding one, e.g. to
+/// exercise the cross-country penalties in tests.
+///
+/// The other logicals use their own exit country, and indices past the end
+/// of `logicals` are ignored. Only the scoring is affected, `Load` doesn't
+/// carry the country.
+pub fn compute_loads_with_country_override(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    overrides: &HashMap<usize, CountryCode>,
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+) -> Result<()> {
+    if overrides.is_empty() {
+        return compute_loads(
+            loads,
+            logicals,
+            status_file,
+            user_location,
+            user_country,
+        );
+    }
+
+    let mut overridden = logicals.to_vec();
+    for (&index, &country) in overrides {
+        if let Some(logical) = overridden.get_mut(index) {
+            logical.exit_country = country;
+        }
+    }
+
+    compute_loads(loads, &overridden, status_file, user_location, user_country)
+}
+
+/// Same as `compute_loads`, but keeps the previous load of servers missing
+/// from the status file instead of resetting them to the default status.
+///
+/// This keeps the list usable when a partial outage of the back end produces
+/// a status file shorter than the previous one. `prev_loads` must have been
+/// computed from the same logicals in the same order. Servers past the end
+/// of `prev_loads` are reset as `compute_loads` does.
+pub fn compute_loads_merge(
+    prev_loads: &[Load],
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+) -> Result<()> {
+    compute_loads(loads, logicals, status_file, user_location, user_country)?;
+
     let statuses = Parser::try_from(status_file)?;
+    for ((load, prev_load), logical) in
+        loads.iter_mut().zip(prev_loads).zip(logicals)
+    {
+        if logical
+            .status_reference
+            .index
+            .to_usize()
+            .is_none_or(|index| index >= statuses.len())
+        {
+            load.clone_from(prev_load);
+        }
+    }
 
-    if loads.len() != logicals.len() {
-        return Err(Error::LengthsNotConsistent {
-            // Errors are used in bindings to other languages so they can't use usize.
-            // The unwraps are ugly, but OTOH we'll never get file with length > u64 and indices are currently 32 bit long.
-            servers: u64::try_from(logicals.len())  // nosemgrep: panic-in-function-returning-result
-                .expect("Unable to convert from usize to u64"),
-            loads: u64::try_from(loads.len())  // nosemgrep: panic-in-function-returning-result
-                .expect("Unable to convert from usize to u64"),
+    Ok(())
+}
+
+/// Same as `compute_loads`, but exponentially smooths the load of each
+/// server with its previous load, as `alpha * load + (1 - alpha) * prev_load`
+/// rounded to the nearest percent.
+///
+/// `alpha` is clamped to 0.0..=1.0, 1.0 keeps the loads from the status file
+/// unsmoothed, and so does a NaN `alpha`. `prev_loads` must have been computed from the same logicals in
+/// the same order, servers past its end are not smoothed.
+///
+/// Only the displayed `Load::load` is smoothed. The score is computed from
+/// the partial score of the status file, which the previous loads don't
+/// carry, so it is the same as the one computed by `compute_loads`.
+pub fn compute_loads_smoothed(
+    prev_loads: &[Load],
+    alpha: f64,
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+) -> Result<()> {
+    compute_loads(loads, logicals, status_file, user_location, user_country)?;
+
+    let alpha = if alpha.is_nan() {
+        1.0
+    } else {
+        alpha.clamp(0.0, 1.0)
+    };
+    for (load, prev_load) in loads.iter_mut().zip(prev_loads) {
+        let smoothed = alpha * f64::from(load.load)
+            + (1.0 - alpha) * f64::from(prev_load.load);
+        // Both loads are u8, so the weighted mean fits in a u8 too.
+        load.load = smoothed.round() as u8;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn compute_loads_impl(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    normalized_jitter: &mut impl FnMut() -> f64,
+) -> Result<()> {
+    config.validate()?;
+    let user_location =
+        resolve_user_location(user_location, user_country, config)?;
+
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        config,
+        &ServerStatus::default(),
+        normalized_jitter,
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        user_travel_distance_legs(&user_location, config.distance_mode),
+    )
+}
+
+/// Same as `compute_loads`, but scores the servers missing from the status
+/// file, or whose status can't be parsed, with the given default status
+/// instead of a disabled one.
+///
+/// For instance, defaulting to an enabled and visible status keeps such
+/// servers selectable during a partial status outage. Returns
+/// `Error::InvalidDefaultStatus` if the default status is not a valid
+/// server status. Servers with an invalid location are still disabled.
+pub fn compute_loads_with_default(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    default: ServerStatus,
+) -> Result<()> {
+    validate_user_location(user_location)?;
+    let default = validate_server(default)
+        .map_err(|error| Error::InvalidDefaultStatus(error.0))?;
+
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        &ScoringConfig::default(),
+        &default,
+        &mut jitter::generator(),
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        user_travel_distance_legs(user_location, DistanceMode::default()),
+    )
+}
+
+/// Same as `compute_loads`, but returns the server status parsing errors
+/// tallied by message instead of logging the first one.
+///
+/// The returned collector also lists every server scored with the default
+/// status, whether its record could not be parsed or is past the end of the
+/// status file.
+pub fn compute_loads_with_stats(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+) -> Result<ParseErrorCollector> {
+    validate_user_location(user_location)?;
+
+    let mut parse_errors = ParseErrorCollector::new();
+    let mut defaulted_servers = Vec::new();
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        &ScoringConfig::default(),
+        &ServerStatus::default(),
+        &mut jitter::generator(),
+        &mut parse_errors.sink(),
+        &mut |logical_index, status_index, reason| {
+            defaulted_servers.push(DefaultedServer {
+                logical_index,
+                status_index,
+                reason: reason.to_owned(),
+            })
+        },
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        user_travel_distance_legs(user_location, DistanceMode::default()),
+    )?;
+
+    for defaulted_server in defaulted_servers {
+        parse_errors.record_defaulted(defaulted_server);
+    }
+
+    Ok(parse_errors)
+}
+
+/// Same as `compute_loads_with_config`, but uses the given travel distances
+/// instead of computing them from the user location.
+///
+/// `distances_km[i]` is the travel distance to logical `i`, in km. This
+/// allows computing the distances separately (e.g. in bulk, or from a
+/// precomputed table) while the rest of the scoring is unchanged. It must
+/// have the same length as `logicals`, and returns
+/// `Error::InvalidDistance` for the first distance that is negative or not
+/// finite, as it would otherwise get the best distance score.
+pub fn compute_loads_with_distances(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    distances_km: &[f64],
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+) -> Result<()> {
+    if distances_km.len() != logicals.len() {
+        return Err(Error::DistancesLengthNotConsistent {
+            servers: logicals.len() as u64,
+            distances: distances_km.len() as u64,
+        });
+    }
+
+    if let Some((index, &distance_km)) =
+        distances_km.iter().enumerate().find(|(_, distance_km)| {
+            !(distance_km.is_finite() && **distance_km >= 0.0)
+        })
+    {
+        return Err(Error::InvalidDistance {
+            index: index as u64,
+            distance_km,
+        });
+    }
+
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        config,
+        &ServerStatus::default(),
+        &mut validated_jitter(config)?,
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        // The supplied distance can't be split, so it all counts as the
+        // client leg.
+        |i, _| (distances_km[i], 0.0),
+    )
+}
+
+/// Same as `compute_loads_with_config`, but caps the bandwidth estimated
+/// from the distance of each server with its capacity.
+///
+/// `max_bandwidths_mbps[i]` is the capacity of logical `i`, in Mbps, or
+/// `None` if it is not known, in which case its score is unchanged. It must
+/// have the same length as `logicals`, and returns
+/// `Error::InvalidMaxBandwidth` for the first capacity that is negative or
+/// NaN.
+pub fn compute_loads_with_max_bandwidths(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    max_bandwidths_mbps: &[Option<f64>],
+) -> Result<()> {
+    if max_bandwidths_mbps.len() != logicals.len() {
+        return Err(Error::MaxBandwidthsLengthNotConsistent {
+            servers: logicals.len() as u64,
+            max_bandwidths: max_bandwidths_mbps.len() as u64,
         });
     }
 
+    if let Some((index, max_bandwidth_mbps)) = max_bandwidths_mbps
+        .iter()
+        .enumerate()
+        .find_map(|(index, max_bandwidth_mbps)| {
+            max_bandwidth_mbps
+                .filter(|max_bandwidth_mbps| {
+                    max_bandwidth_mbps.is_nan() || *max_bandwidth_mbps < 0.0
+                })
+                .map(|max_bandwidth_mbps| (index, max_bandwidth_mbps))
+        })
+    {
+        return Err(Error::InvalidMaxBandwidth {
+            index: index as u64,
+            max_bandwidth_mbps,
+        });
+    }
+
+    let user_location =
+        resolve_user_location(user_location, user_country, config)?;
+
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        config,
+        &ServerStatus::default(),
+        &mut validated_jitter(config)?,
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |i, _| ServerExtras {
+            max_bandwidth_mbps: max_bandwidths_mbps[i],
+            ..Default::default()
+        },
+        user_travel_distance_legs(&user_location, config.distance_mode),
+    )
+}
+
+/// Same as calling `compute_loads` for each `(user_location, user_country)`
+/// of `users`, but parses the status file and resolves the status of each
+/// server only once.
+///
+/// Returns the loads of the logicals for each user, in the order of `users`.
+/// Returns an error if any user location is invalid.
+pub fn compute_loads_batch(
+    users: &[(Option<Location>, Option<CountryCode>)],
+    logicals: &[Logical],
+    status_file: &[u8],
+) -> Result<Vec<Vec<Load>>> {
+    for (user_location, _) in users {
+        validate_user_location(user_location)?;
+    }
+
+    let statuses = Parser::try_from(status_file)?;
+    let config = ScoringConfig::default();
+    let mut report_parsing_error = first_parsing_error_logger();
+
+    // The statuses don't depend on the user. As in `compute_loads`, servers
+    // with an invalid location are scored as unknown at no distance, `None`.
+    let status_is_unknown = ServerStatus::default();
+    let resolved = logicals
+        .iter()
+        .map(|logical| {
+            has_scoreable_locations(logical).then(|| {
+                statuses.get_for(
+                    logical,
+                    &status_is_unknown,
+                    &mut report_parsing_error,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
     let mut normalized_jitter = jitter::generator();
+    let batch = users
+        .iter()
+        .map(|(user_location, user_country)| {
+            let user_location =
+                config.user_location(user_location, user_country);
+            let mut travel_distance_legs =
+                user_travel_distance_legs(&user_location, config.distance_mode);
+
+            std::iter::zip(logicals, &resolved)
+                .enumerate()
+                .map(|(i, (logical, status))| {
+                    let (status, legs) = match status {
+                        Some(status) => {
+                            (status, travel_distance_legs(i, logical))
+                        }
+                        None => (&status_is_unknown, (0.0, 0.0)),
+                    };
+
+                    let mut load = Load::default();
+                    compute_load_from_legs(
+                        &mut load,
+                        logical,
+                        status,
+                        legs,
+                        ServerExtras::default(),
+                        user_country,
+                        &config,
+                        normalized_jitter(),
+                    );
+                    load
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(batch)
+}
+
+/// Same as `compute_loads`, but blends the live partial score of each
+/// server with its historical one, as `weight * historical + (1 - weight) *
+/// live`, before scoring it.
+///
+/// `historical_partial_scores[i]` is the historical partial score of logical
+/// `i`, it must have the same length as `logicals` unless it is empty, in
+/// which case the loads are computed exactly as `compute_loads` does.
+/// `weight` and the historical partial scores are clamped to 0.0..=1.0, a
+/// NaN weight or a non-finite historical partial score is ignored.
+pub fn compute_loads_blended(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    historical_partial_scores: &[f32],
+    weight: f64,
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+) -> Result<()> {
+    if historical_partial_scores.is_empty() {
+        return compute_loads(
+            loads,
+            logicals,
+            status_file,
+            user_location,
+            user_country,
+        );
+    }
+
+    if historical_partial_scores.len() != logicals.len() {
+        return Err(Error::HistoricalLengthNotConsistent {
+            servers: logicals.len() as u64,
+            historical: historical_partial_scores.len() as u64,
+        });
+    }
+
+    let config = ScoringConfig::default();
+    let user_location =
+        resolve_user_location(user_location, user_country, &config)?;
+    let weight = if weight.is_nan() {
+        0.0
+    } else {
+        weight.clamp(0.0, 1.0)
+    };
+
+    compute_loads_from_legs(
+        loads,
+        logicals,
+        status_file,
+        user_country,
+        &config,
+        &ServerStatus::default(),
+        &mut jitter::generator(),
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |i, status| {
+            let historical = f64::from(historical_partial_scores[i]);
+            if !historical.is_finite() {
+                return;
+            }
+
+            let blended = weight * historical.clamp(0.0, 1.0)
+                + (1.0 - weight) * f64::from(status.partial_score);
+            status.partial_score = blended.clamp(0.0, 1.0) as f32;
+        },
+        &mut |_, _| ServerExtras::default(),
+        user_travel_distance_legs(&user_location, config.distance_mode),
+    )
+}
+
+// Validates the configuration, then returns a generator of the jitter range
+// of the configuration.
+fn validated_jitter(config: &ScoringConfig) -> Result<impl FnMut() -> f64> {
+    config.validate()?;
+
+    jitter::generator_with(&config.jitter)
+}
+
+// Returns the travel distance legs from the user location to each logical.
+fn user_travel_distance_legs(
+    user_location: &Option<Location>,
+    mode: DistanceMode,
+) -> impl FnMut(usize, &Logical) -> (f64, f64) + '_ {
+    move |_, logical| {
+        compute_user_travel_distance_legs(
+            &logical.exit_location,
+            &logical.entry_location,
+            user_location,
+            mode,
+        )
+    }
+}
+
+// Scores the servers with the (client leg, internal leg) travel distances
+// returned by `travel_distance_legs` for each logical, into `loads`. The
+// other parameters are the ones of `score_servers`.
+#[allow(clippy::too_many_arguments)]
+fn compute_loads_from_legs(
+    loads: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    default_status: &ServerStatus,
+    normalized_jitter: &mut impl FnMut() -> f64,
+    log_parsing_error: &mut impl FnMut(usize, usize, &str),
+    record_defaulted: &mut impl FnMut(usize, ServerIndex, &str),
+    adjust_status: &mut impl FnMut(usize, &mut ServerStatus),
+    extras: &mut impl FnMut(usize, &Logical) -> ServerExtras,
+    mut travel_distance_legs: impl FnMut(usize, &Logical) -> (f64, f64),
+) -> Result<()> {
+    if loads.len() != logicals.len() {
+        return Err(lengths_not_consistent(logicals.len(), loads.len()));
+    }
+
+    score_servers(
+        logicals.iter().enumerate(),
+        status_file,
+        user_country,
+        config,
+        default_status,
+        normalized_jitter,
+        log_parsing_error,
+        record_defaulted,
+        adjust_status,
+        extras,
+        &mut |i, logical, server, normalized_jitter| {
+            server
+                .score(
+                    travel_distance_legs(i, logical),
+                    normalized_jitter,
+                    config,
+                )
+                .write_to(&mut loads[i]);
+        },
+    )
+}
+
+// The scoring loop shared by the entry points. Resolves the status and the
+// penalty of each of the `(index, logical)` pairs of `servers` once, and
+// passes the resolved server and its jitter to `score`, which scores it at
+// its travel distance and writes the result to its output.
+//
+// The servers whose status can't be resolved get `default_status`.
+// `adjust_status` can modify the status of each logical, once resolved,
+// before it is scored, and `extras` returns the scoring inputs of each
+// logical that don't come from the status file.
+#[allow(clippy::too_many_arguments)]
+fn score_servers<'a>(
+    servers: impl Iterator<Item = (usize, &'a Logical)>,
+    status_file: &[u8],
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    default_status: &ServerStatus,
+    normalized_jitter: &mut impl FnMut() -> f64,
+    log_parsing_error: &mut impl FnMut(usize, usize, &str),
+    record_defaulted: &mut impl FnMut(usize, ServerIndex, &str),
+    adjust_status: &mut impl FnMut(usize, &mut ServerStatus),
+    extras: &mut impl FnMut(usize, &Logical) -> ServerExtras,
+    score: &mut impl FnMut(usize, &Logical, &ResolvedServer, f64),
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span =
+        tracing::debug_span!("compute_loads", servers = tracing::field::Empty)
+            .entered();
+
+    let statuses = {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("parse").entered();
+
+        Parser::try_from(status_file)?
+    };
+
+    #[cfg(feature = "tracing")]
+    let mut stats = ComputeStats::default();
+    #[cfg(feature = "tracing")]
+    let mut parse_errors = 0;
 
-    let mut error_reported = false;
     let mut report_parsing_error =
-        move |index: usize, byte_offset: usize, error_msg: &str| {
-            if !error_reported {
-                error_reported = true;
+        |index: usize, byte_offset: usize, error_msg: &str| {
+            #[cfg(feature = "tracing")]
+            {
+                parse_errors += 1;
+            }
+
+            log_parsing_error(index, byte_offset, error_msg);
+        };
+
+    #[cfg(feature = "tracing")]
+    let scoring_span = tracing::trace_span!("scoring").entered();
+
+    let mut invalid_location_reported = false;
+    for (i, logical) in servers {
+        let status_index = logical.status_reference.index;
+
+        // Servers with an invalid location are scored as disabled by
+        // `ResolvedServer`, they are only reported here.
+        let status = if has_scoreable_locations(logical) {
+            if status_index
+                .to_usize()
+                .is_none_or(|index| index >= statuses.len())
+            {
+                record_defaulted(i, status_index, STATUS_INDEX_OUT_OF_RANGE);
+            }
+
+            // Obtain the status from the binary status file
+            let mut status = statuses.get_for(
+                logical,
+                default_status,
+                &mut |index, byte_offset, error_msg| {
+                    report_parsing_error(index, byte_offset, error_msg);
+                    record_defaulted(i, status_index, error_msg);
+                },
+            );
+            adjust_status(i, &mut status);
+            status
+        } else {
+            if !invalid_location_reported {
+                invalid_location_reported = true;
                 log::warn!(
-                    "Failed to parse server status at index {index} with bytes offset {byte_offset}: {error_msg}. \
-                    Using default status for this server. \
-                    Further server status parsing errors will be ignored.",
+                    "Logical {i} has an invalid location. \
+                    Scoring it as disabled. \
+                    Further invalid locations will be ignored."
                 );
             }
-        };
+            record_defaulted(i, status_index, INVALID_SERVER_LOCATION);
 
-    let status_is_unknown = super::status::ServerStatus::default();
-    for (load, logical) in std::iter::zip(loads, logicals) {
-        // Obtain the status from the binary status file
-        let status = statuses.get(
-            logical.status_reference.index as usize,
-            &status_is_unknown,
-            &mut report_parsing_error,
-        );
+            ServerStatus::default()
+        };
 
-        // Compute the score
-        let score = compute_score(
-            ComputeScoreServerParams {
-                status_penalty: logical.status_reference.penalty,
-                status_cost: logical.status_reference.cost,
-                country: logical.exit_country,
-                partial_score: status.partial_score as f64,
-                status: status.status,
-                exit_location: &logical.exit_location,
-                entry_location: &logical.entry_location,
-                normalized_jitter: normalized_jitter(),
-                #[cfg(feature = "debug")]
-                debug: &mut load.debug,
-            },
-            user_location,
+        let server = ResolvedServer::new(
+            logical,
+            &status,
+            extras(i, logical),
             user_country,
+            config,
         );
 
-        load.is_enabled = status.status & STATUS_ENABLED != 0;
-        load.is_visible = status.status & STATUS_VISIBLE != 0;
-        load.is_autoconnectable = status.status & STATUS_AUTOCONNECTABLE != 0;
-        load.load = status.load;
-        load.score = score;
+        #[cfg(feature = "tracing")]
+        {
+            let status = server.status.status;
+            stats.servers += 1;
+            stats.enabled += usize::from(status & STATUS_ENABLED != 0);
+            stats.visible += usize::from(status & STATUS_VISIBLE != 0);
+            stats.autoconnectable +=
+                usize::from(status & STATUS_AUTOCONNECTABLE != 0);
+        }
+
+        score(i, logical, &server, normalized_jitter());
     }
 
-    Ok(())
-}
+    #[cfg(feature = "tracing")]
+    {
+        drop(scoring_span);
 
-#[cfg(test)]
-mod tests {
-    use std::vec;
+        stats.parse_errors = parse_errors;
+        _span.record("servers", stats.servers);
+        tracing::debug!(
+            enabled = stats.enabled,
+            visible = stats.visible,
+            autoconnectable = stats.autoconnectable,
+            parse_errors = stats.parse_errors,
+            "computed loads"
+        );
+    }
 
-    use super::*;
-    use crate::country_code::CountryCode;
-    use crate::location::Location;
-    use crate::logical::StatusReference;
+    Ok(())
+}
 
-    fn create_dummy_location() -> Location {
-        Location {
-            latitude: 0.0,
-            longitude: 0.0,
+/// Same as `compute_loads_with_config`, but writes the results into
+/// parallel columns instead of an array of `Load`.
+///
+/// `out_flags[i]` contains the `STATUS_ENABLED`, `STATUS_VISIBLE` and
+/// `STATUS_AUTOCONNECTABLE` bits of server `i`. All output slices must have
+/// the same length as `logicals`.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_loads_columnar(
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    out_scores: &mut [f64],
+    out_loads: &mut [u8],
+    out_flags: &mut [u8],
+) -> Result<()> {
+    for len in [out_scores.len(), out_loads.len(), out_flags.len()] {
+        if len != logicals.len() {
+            return Err(lengths_not_consistent(logicals.len(), len));
         }
     }
 
-    fn create_dummy_country() -> CountryCode {
-        CountryCode::try_from(b"CH").expect("Invalid country code")
+    let user_location =
+        resolve_user_location(user_location, user_country, config)?;
+    let mut travel_distance_legs =
+        user_travel_distance_legs(&user_location, config.distance_mode);
+
+    score_servers(
+        logicals.iter().enumerate(),
+        status_file,
+        user_country,
+        config,
+        &ServerStatus::default(),
+        &mut validated_jitter(config)?,
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        &mut |i, logical, server, normalized_jitter| {
+            let scored = server.score(
+                travel_distance_legs(i, logical),
+                normalized_jitter,
+                config,
+            );
+
+            out_scores[i] = scored.score;
+            out_loads[i] = scored.load;
+            out_flags[i] = scored.status
+                & (STATUS_ENABLED | STATUS_VISIBLE | STATUS_AUTOCONNECTABLE);
+        },
+    )
+}
+
+/// Same as `compute_loads_with_config`, but computes the loads with both the
+/// `DistanceMode::Legacy` and `DistanceMode::EntryFirst` travel distances,
+/// whatever the `distance_mode` of the configuration.
+///
+/// The status file is parsed, and the status and penalty of each server are
+/// resolved, once for both loads. Both loads of a server also share the same
+/// jitter, so they only differ by their travel distance. Both output slices
+/// must have the same length as `logicals`.
+pub fn compute_loads_dual(
+    loads_legacy: &mut [Load],
+    loads_new: &mut [Load],
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+) -> Result<()> {
+    for len in [loads_legacy.len(), loads_new.len()] {
+        if len != logicals.len() {
+            return Err(lengths_not_consistent(logicals.len(), len));
+        }
     }
 
-    #[test]
-    fn test_compute_loads_error_lengths_not_consistent() {
-        let lengths = [(1, 2), (1, 2)];
+    let user_location =
+        resolve_user_location(user_location, user_country, config)?;
 
-        for (loads_len, servers_len) in lengths {
-            let mut loads = Vec::new();
-            loads.resize(loads_len, Load::default());
+    score_servers(
+        logicals.iter().enumerate(),
+        status_file,
+        user_country,
+        config,
+        &ServerStatus::default(),
+        &mut validated_jitter(config)?,
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        &mut |i, logical, server, normalized_jitter| {
+            for (loads, mode) in [
+                (&mut *loads_legacy, DistanceMode::Legacy),
+                (&mut *loads_new, DistanceMode::EntryFirst),
+            ] {
+                let travel_distance_legs_km = compute_user_travel_distance_legs(
+                    &logical.exit_location,
+                    &logical.entry_location,
+                    &user_location,
+                    mode,
+                );
 
-            let mut servers = Vec::new();
-            servers.resize(servers_len, Logical::default());
+                server
+                    .score(travel_distance_legs_km, normalized_jitter, config)
+                    .write_to(&mut loads[i]);
+            }
+        },
+    )
+}
+
+/// Same as `compute_loads_with_config`, but only scores the logicals for
+/// which `keep` returns `true`.
+///
+/// `loads` is replaced with the loads of the retained logicals, in their
+/// original order. The returned vector maps each position in `loads` to the
+/// index of its logical in `logicals`.
+pub fn compute_loads_filtered(
+    loads: &mut Vec<Load>,
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    keep: impl Fn(&Logical) -> bool,
+) -> Result<Vec<usize>> {
+    let user_location =
+        resolve_user_location(user_location, user_country, config)?;
+    let mut travel_distance_legs =
+        user_travel_distance_legs(&user_location, config.distance_mode);
+
+    loads.clear();
+    let mut indices = Vec::new();
+    score_servers(
+        logicals
+            .iter()
+            .enumerate()
+            .filter(|(_, logical)| keep(logical)),
+        status_file,
+        user_country,
+        config,
+        &ServerStatus::default(),
+        &mut validated_jitter(config)?,
+        &mut first_parsing_error_logger(),
+        &mut |_, _, _| {},
+        &mut |_, _| {},
+        &mut |_, _| ServerExtras::default(),
+        &mut |i, logical, server, normalized_jitter| {
+            let mut load = Load::default();
+            server
+                .score(
+                    travel_distance_legs(i, logical),
+                    normalized_jitter,
+                    config,
+                )
+                .write_to(&mut load);
+
+            loads.push(load);
+            indices.push(i);
+        },
+    )?;
+
+    Ok(indices)
+}
+
+// Computes a single server from its resolved status.
+pub(crate) fn compute_load(
+    load: &mut Load,
+    logical: &Logical,
+    status: &ServerStatus,
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    normalized_jitter: f64,
+) {
+    let travel_distance_legs_km = compute_user_travel_distance_legs(
+        &logical.exit_location,
+        &logical.entry_location,
+        user_location,
+        config.distance_mode,
+    );
+
+    compute_load_from_legs(
+        load,
+        logical,
+        status,
+        travel_distance_legs_km,
+        ServerExtras::default(),
+        user_country,
+        config,
+        normalized_jitter,
+    );
+}
+
+/// Scores a server as if the status file reported the given load and
+/// partial score for it, without jitter.
+///
+/// The server is assumed to be enabled and visible, so that only the
+/// distance and the load weigh in the score. As with `compute_loads`, the
+/// score depends on the partial score, the load itself only matters for the
+/// displayed `Load::load`. Returns an error if the user location is invalid.
+pub fn score_at_load(
+    logical: &Logical,
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    load: u8,
+    partial_score: f32,
+    config: &ScoringConfig,
+) -> Result<f64> {
+    config.validate()?;
+    let status = ServerStatus {
+        status: STATUS_ENABLED | STATUS_VISIBLE,
+        load,
+        partial_score,
+        ..Default::default()
+    };
+
+    let mut scored = Load::default();
+    compute_load(
+        &mut scored,
+        logical,
+        &status,
+        &resolve_user_location(user_location, user_country, config)?,
+        user_country,
+        config,
+        0.0,
+    );
+
+    Ok(scored.score.value())
+}
+
+/// Scores a server that isn't in the fleet yet, as if the status file
+/// reported `projected_status` for it, and returns its load along with the
+/// 0-based rank it would have among `existing_loads`.
+///
+/// The server is scored without jitter. It ranks after the existing servers
+/// with the same score, as ties are broken by index and it would be added
+/// last. `existing_loads` doesn't need to be sorted. Returns an error if
+/// the user location is invalid.
+pub fn project_server(
+    new: &Logical,
+    projected_status: &ServerStatus,
+    existing_loads: &[Load],
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+) -> Result<(Load, usize)> {
+    config.validate()?;
+    let mut projected = Load::default();
+    compute_load(
+        &mut projected,
+        new,
+        projected_status,
+        &resolve_user_location(user_location, user_country, config)?,
+        user_country,
+        config,
+        0.0,
+    );
+
+    let mut scores = existing_loads
+        .iter()
+        .map(|load| load.score)
+        .collect::<Vec<_>>();
+    scores.sort_unstable();
+    let rank = scores.partition_point(|score| *score <= projected.score);
+
+    Ok((projected, rank))
+}
+
+// Returns whether the locations of the logical can be scored.
+fn has_scoreable_locations(logical: &Logical) -> bool {
+    logical.exit_location.is_scoreable()
+        && logical.entry_location.is_scoreable()
+}
+
+// The scoring inputs of a server that don't come from the status file.
+#[derive(Debug, Default, Clone, Copy)]
+struct ServerExtras {
+    // Added to the status penalty of the server.
+    penalty: f64,
+    // The capacity of the server, which caps its distance bandwidth estimate.
+    max_bandwidth_mbps: Option<f64>,
+}
+
+// Same as `compute_load`, with the (client leg, internal leg) travel
+// distances and the extra scoring inputs of the server already computed.
+#[allow(clippy::too_many_arguments)]
+fn compute_load_from_legs(
+    load: &mut Load,
+    logical: &Logical,
+    status: &ServerStatus,
+    travel_distance_legs_km: (f64, f64),
+    extras: ServerExtras,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+    normalized_jitter: f64,
+) {
+    ResolvedServer::new(logical, status, extras, user_country, config)
+        .score(travel_distance_legs_km, normalized_jitter, config)
+        .write_to(load);
+}
+
+// A server whose status and penalty are resolved, so that it can be scored
+// at any travel distance.
+//
+// Every entry point scores its servers through it, so that a server with an
+// invalid location, which would get a NaN score, is always scored as unknown
+// (i.e. disabled) at no distance instead.
+struct ResolvedServer<'a> {
+    logical: &'a Logical,
+    status: ServerStatus,
+    has_scoreable_locations: bool,
+    status_penalty: f64,
+    max_bandwidth_mbps: Option<f64>,
+    penalty: f64,
+}
+
+impl<'a> ResolvedServer<'a> {
+    fn new(
+        logical: &'a Logical,
+        status: &ServerStatus,
+        extras: ServerExtras,
+        user_country: &Option<CountryCode>,
+        config: &ScoringConfig,
+    ) -> Self {
+        let has_scoreable_locations = has_scoreable_locations(logical);
+        let status = if has_scoreable_locations {
+            status.clone()
+        } else {
+            ServerStatus::default()
+        };
+
+        let status_penalty = config
+            .status_penalty_precedence
+            .resolve(logical.status_reference.penalty, status.status_penalty)
+            + extras.penalty;
+        let (penalty, _) = compute_penalty(
+            status_penalty,
+            logical.status_reference.cost,
+            status.partial_score as f64,
+            user_country,
+            logical.exit_country,
+            status.status,
+            config,
+        );
+
+        Self {
+            logical,
+            status,
+            has_scoreable_locations,
+            status_penalty,
+            max_bandwidth_mbps: extras.max_bandwidth_mbps,
+            penalty,
+        }
+    }
+
+    // Scores the server with the given travel distances and jitter, only
+    // the base score depends on them.
+    fn score(
+        &self,
+        travel_distance_legs_km: (f64, f64),
+        normalized_jitter: f64,
+        config: &ScoringConfig,
+    ) -> ScoredServer {
+        let (client_leg_km, internal_leg_km) = if self.has_scoreable_locations {
+            travel_distance_legs_km
+        } else {
+            (0.0, 0.0)
+        };
+
+        // The actual distance is displayed, the weighted one is scored
+        let travel_distance_km = client_leg_km + internal_leg_km;
+        let scored_distance_km =
+            client_leg_km + config.internal_leg_weight * internal_leg_km;
+
+        let breakdown = ScoreBreakdown {
+            base_score: compute_base_score(
+                &ComputeScoreServerParams {
+                    status_penalty: self.status_penalty,
+                    status_cost: self.logical.status_reference.cost,
+                    country: self.logical.exit_country,
+                    partial_score: self.status.partial_score as f64,
+                    status: self.status.status,
+                    travel_distance_km: scored_distance_km,
+                    normalized_jitter,
+                    max_bandwidth_mbps: self.max_bandwidth_mbps,
+                },
+                config,
+            ),
+            penalty: self.penalty,
+        };
+
+        ScoredServer {
+            status: self.status.status,
+            load: config.round_load(self.status.load),
+            score: config.clamp_score(breakdown.score()),
+            penalty: breakdown.penalty,
+            display_fraction: if config.compute_display_fraction {
+                breakdown.display_fraction()
+            } else {
+                0.0
+            },
+            distance_km: travel_distance_km,
+            #[cfg(feature = "debug")]
+            debug: LoadDebugFields {
+                partial_score: self.status.partial_score as f64,
+                client_leg_km,
+                internal_leg_km,
+            },
+        }
+    }
+}
+
+// The result of scoring a server, before it is written to its output.
+struct ScoredServer {
+    status: u8,
+    load: u8,
+    score: f64,
+    penalty: f64,
+    display_fraction: f64,
+    distance_km: f64,
+    // Additional debug information
+    #[cfg(feature = "debug")]
+    debug: LoadDebugFields,
+}
+
+impl ScoredServer {
+    fn write_to(self, load: &mut Load) {
+        load.is_enabled = self.status & STATUS_ENABLED != 0;
+        load.is_visible = self.status & STATUS_VISIBLE != 0;
+        load.is_autoconnectable = self.status & STATUS_AUTOCONNECTABLE != 0;
+        load.status = self.status;
+        load.load = self.load;
+        load.score = Score(self.score);
+        load.penalty = self.penalty;
+        load.distance_km = self.distance_km;
+        load.display_fraction = self.display_fraction;
+        #[cfg(feature = "debug")]
+        {
+            load.debug = self.debug;
+        }
+    }
+}
+
+pub(crate) fn validate_user_location(
+    user_location: &Option<Location>,
+) -> Result<()> {
+    if let Some(location) = user_location {
+        if !location.is_scoreable() {
+            return Err(Error::InvalidUserLocation {
+                latitude: location.latitude,
+                longitude: location.longitude,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Validates the user location, then resolves it with the configuration, e.g.
+// to the centroid of the user country when it is unknown.
+pub(crate) fn resolve_user_location(
+    user_location: &Option<Location>,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+) -> Result<Option<Location>> {
+    validate_user_location(user_location)?;
+
+    Ok(config.user_location(user_location, user_country))
+}
+
+pub(crate) fn lengths_not_consistent(servers: usize, loads: usize) -> Error {
+    Error::LengthsNotConsistent {
+        // Errors are used in bindings to other languages so they can't use usize.
+        // The unwraps are ugly, but OTOH we'll never get file with length > u64 and indices are currently 32 bit long.
+        servers: u64::try_from(servers) // nosemgrep: panic-in-function-returning-result
+            .expect("Unable to convert from usize to u64"),
+        loads: u64::try_from(loads) // nosemgrep: panic-in-function-returning-result
+            .expect("Unable to convert from usize to u64"),
+    }
+}
+
+// Logs the first server status parsing error, and ignores the others to
+// avoid flooding the logs.
+pub(crate) fn first_parsing_error_logger() -> impl FnMut(usize, usize, &str) {
+    let mut error_reported = false;
+    move |index: usize, byte_offset: usize, error_msg: &str| {
+        if !error_reported {
+            error_reported = true;
+            log::warn!(
+                "Failed to parse server status at index {index} with bytes offset {byte_offset}: {error_msg}. \
+                Using default status for this server. \
+                Further server status parsing errors will be ignored.",
+            );
+        }
+    }
+}
+
+// Tallies reported at the end of `compute_loads` when tracing is enabled.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+struct ComputeStats {
+    servers: usize,
+    enabled: usize,
+    visible: usize,
+    autoconnectable: usize,
+    parse_errors: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use super::*;
+    use crate::compute_score::{
+        compute_user_travel_distance, UNAVAILABLE_PENALTY,
+    };
+    use crate::country_code::CountryCode;
+    use crate::location::Location;
+    use crate::logical::StatusReference;
+    use crate::test_fixtures::{make_logical, make_logicals, make_status_file};
+    use crate::StatusPenaltyPrecedence;
+
+    fn create_dummy_location() -> Location {
+        Location {
+            latitude: 0.0,
+            longitude: 0.0,
+        }
+    }
+
+    fn create_dummy_country() -> CountryCode {
+        CountryCode::try_from(b"CH").expect("Invalid country code")
+    }
+
+    #[test]
+    fn test_compute_loads_error_lengths_not_consistent() {
+        let lengths = [(1, 2), (1, 2)];
+
+        for (loads_len, servers_len) in lengths {
+            let mut loads = Vec::new();
+            loads.resize(loads_len, Load::default());
+
+            let mut servers = Vec::new();
+            servers.resize(servers_len, Logical::default());
 
             let error = compute_loads(
                 &mut loads,
@@ -129,14 +1409,904 @@ mod tests {
             )
             .unwrap_err();
 
-            match error {
-                Error::LengthsNotConsistent { servers, loads } => {
-                    assert_eq!(servers, 2);
-                    assert_eq!(loads, 1);
-                }
-                _ => panic!("Expected LengthsNotConsistent error"),
-            }
-        }
+            match error {
+                Error::LengthsNotConsistent { servers, loads } => {
+                    assert_eq!(servers, 2);
+                    assert_eq!(loads, 1);
+                }
+                _ => panic!("Expected LengthsNotConsistent error"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_loads_error_invalid_user_location() {
+        let invalid_locations = [
+            (f32::NAN, 0.0),
+            (0.0, f32::INFINITY),
+            (90.5, 0.0),
+            (0.0, -180.5),
+        ];
+
+        for (latitude, longitude) in invalid_locations {
+            let mut loads = vec![Load::default()];
+
+            let error = compute_loads(
+                &mut loads,
+                &[Logical::default()],
+                &[1_u8, 0_u8, 0_u8, 0_u8],
+                &Some(Location {
+                    latitude,
+                    longitude,
+                }),
+                &Some(create_dummy_country()),
+            )
+            .unwrap_err();
+
+            match error {
+                Error::InvalidUserLocation { .. } => {}
+                _ => panic!("Expected InvalidUserLocation error"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_loads_checked_duplicate_status_index() {
+        let status_file = make_status_file(&[(3, 0, 0.0), (3, 0, 0.0)]);
+        let mut loads = vec![Load::default(); 3];
+
+        let error = compute_loads_checked(
+            &mut loads,
+            &[make_logical(0), make_logical(1), make_logical(0)],
+            &status_file,
+            &None,
+            &None,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Logicals 0 and 2 reference the same status index 0."
+        );
+
+        let mut loads = vec![Load::default(); 2];
+        compute_loads_checked(
+            &mut loads,
+            &[make_logical(1), make_logical(0)],
+            &status_file,
+            &None,
+            &None,
+        )
+        .expect("Distinct status indices should be accepted");
+        assert!(loads.iter().all(|load| load.is_enabled));
+    }
+
+    #[test]
+    fn test_compute_loads_with_country_caps() {
+        let logical = |index, country: &[u8; 2]| Logical {
+            exit_country: CountryCode::try_from(country).unwrap(),
+            ..make_logical(index)
+        };
+        let logicals =
+            [logical(0, b"CH"), logical(1, b"CH"), logical(2, b"FR")];
+        let status_file =
+            make_status_file(&[(3, 60, 0.5), (3, 90, 0.5), (3, 90, 0.5)]);
+        let compute = |caps: &HashMap<CountryCode, f64>| {
+            let mut loads = vec![Load::default(); logicals.len()];
+            compute_loads_with_country_caps(
+                &mut loads,
+                &logicals,
+                &status_file,
+                &None,
+                &None,
+                caps,
+            )
+            .expect("Failed to compute loads");
+            loads
+        };
+
+        assert!(!compute(&HashMap::new()).iter().any(Load::is_penalized));
+
+        // The mean load in CH is 75, FR has no cap.
+        let capped = compute(&HashMap::from([(create_dummy_country(), 70.0)]));
+        assert_eq!(
+            capped.iter().map(Load::is_penalized).collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+        // The penalty is part of the score, not added to it afterwards.
+        for load in &capped[..2] {
+            assert_eq!(load.penalty, OVER_CAPACITY_PENALTY);
+            assert!(load.base_score() < 1.0);
+        }
+
+        let capped = compute(&HashMap::from([(create_dummy_country(), 75.0)]));
+        assert!(!capped.iter().any(Load::is_penalized));
+    }
+
+    #[test]
+    fn test_compute_loads_merge() {
+        let logicals = make_logicals(3);
+        let prev_loads = vec![
+            Load {
+                is_enabled: true,
+                is_visible: true,
+                load: 10,
+                score: Score(0.5),
+                ..Default::default()
+            };
+            2
+        ];
+        let status_file = make_status_file(&[(3, 42, 0.0)]);
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads_merge(
+            &prev_loads,
+            &mut loads,
+            &logicals,
+            &status_file,
+            &None,
+            &None,
+        )
+        .expect("Failed to compute loads");
+
+        // Server 0 is in the status file, server 1 is kept from the previous
+        // loads and server 2 has no previous load.
+        assert_eq!(loads[0].load, 42);
+        assert_eq!(loads[1], prev_loads[1]);
+        assert!(!loads[2].is_enabled);
+    }
+
+    #[test]
+    fn test_compute_loads_smoothed() {
+        let logicals = make_logicals(3);
+        let prev_loads = [10, 90].map(|load| Load {
+            load,
+            ..Default::default()
+        });
+        let status_file =
+            make_status_file(&[(3, 50, 0.0), (3, 50, 0.0), (3, 50, 0.0)]);
+
+        let smoothed = |alpha| {
+            let mut loads = vec![Load::default(); logicals.len()];
+            compute_loads_smoothed(
+                &prev_loads,
+                alpha,
+                &mut loads,
+                &logicals,
+                &status_file,
+                &None,
+                &None,
+            )
+            .expect("Failed to compute loads");
+            loads.iter().map(|load| load.load).collect::<Vec<_>>()
+        };
+
+        // Server 2 has no previous load
+        assert_eq!(smoothed(0.25), [20, 80, 50]);
+        assert_eq!(smoothed(1.0), [50, 50, 50]);
+        assert_eq!(smoothed(0.0), [10, 90, 50]);
+        assert_eq!(smoothed(-1.0), smoothed(0.0));
+        assert_eq!(smoothed(f64::NAN), smoothed(1.0));
+    }
+
+    #[test]
+    fn test_score_at_load() {
+        let logical = Logical {
+            exit_country: create_dummy_country(),
+            ..Default::default()
+        };
+        let user_country = Some(create_dummy_country());
+        let config = ScoringConfig::default();
+
+        let score = |partial_score| {
+            score_at_load(
+                &logical,
+                &None,
+                &user_country,
+                50,
+                partial_score,
+                &config,
+            )
+            .expect("Failed to score the server")
+        };
+
+        assert_eq!(score(0.25), 0.25);
+        assert!(score(0.25) < score(0.75));
+        // A saturated server is penalized
+        assert!(score(1.0) > 1.0);
+    }
+
+    #[test]
+    fn test_score_at_load_and_project_server_user_location() {
+        let logical = Logical {
+            exit_location: Location {
+                latitude: 48.8566, // Paris
+                longitude: 2.3522,
+            },
+            entry_location: Location {
+                latitude: 48.8566,
+                longitude: 2.3522,
+            },
+            exit_country: create_dummy_country(),
+            ..Default::default()
+        };
+        let status = ServerStatus {
+            status: STATUS_ENABLED | STATUS_VISIBLE,
+            load: 10,
+            partial_score: 0.0,
+            ..Default::default()
+        };
+        let user_country = Some(create_dummy_country());
+        let config = ScoringConfig {
+            country_centroid_fallback: true,
+            ..Default::default()
+        };
+
+        // Both fall back to the centroid of the user country.
+        let score = score_at_load(
+            &logical,
+            &None,
+            &user_country,
+            status.load,
+            status.partial_score,
+            &config,
+        )
+        .expect("Failed to score the server");
+        let (projected, _) = project_server(
+            &logical,
+            &status,
+            &[],
+            &None,
+            &user_country,
+            &config,
+        )
+        .expect("Failed to project the server");
+        assert!(projected.distance_km > 0.0);
+        assert_eq!(score, projected.score.value());
+
+        // Both reject an invalid user location.
+        let invalid = Some(Location {
+            latitude: f32::NAN,
+            longitude: 0.0,
+        });
+        assert!(matches!(
+            score_at_load(&logical, &invalid, &None, 10, 0.0, &config),
+            Err(Error::InvalidUserLocation { .. })
+        ));
+        assert!(matches!(
+            project_server(&logical, &status, &[], &invalid, &None, &config),
+            Err(Error::InvalidUserLocation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compute_loads_batch() {
+        let logical = |index, latitude| Logical {
+            exit_location: Location {
+                latitude,
+                longitude: 2.3522,
+            },
+            entry_location: Location {
+                latitude,
+                longitude: 2.3522,
+            },
+            exit_country: create_dummy_country(),
+            ..make_logical(index)
+        };
+        let logicals = [
+            logical(0, 48.8566),
+            logical(1, 43.6047),
+            logical(2, f32::NAN),
+        ];
+        let status_file =
+            make_status_file(&[(3, 10, 0.25), (3, 20, 0.25), (3, 30, 0.25)]);
+        let users = [
+            (
+                Some(Location {
+                    latitude: 51.5074, // London
+                    longitude: -0.1278,
+                }),
+                Some(create_dummy_country()),
+            ),
+            (
+                Some(Location {
+                    latitude: 41.3874, // Barcelona
+                    longitude: 2.1686,
+                }),
+                None,
+            ),
+            (None, None),
+        ];
+
+        let batch = compute_loads_batch(&users, &logicals, &status_file)
+            .expect("Failed to compute loads");
+
+        assert_eq!(batch.len(), users.len());
+        for ((user_location, user_country), batch_loads) in
+            std::iter::zip(&users, &batch)
+        {
+            let mut loads = vec![Load::default(); logicals.len()];
+            compute_loads(
+                &mut loads,
+                &logicals,
+                &status_file,
+                user_location,
+                user_country,
+            )
+            .expect("Failed to compute loads");
+
+            for (load, batch_load) in std::iter::zip(&loads, batch_loads) {
+                assert_eq!(load.load, batch_load.load);
+                assert_eq!(load.is_enabled, batch_load.is_enabled);
+                assert_eq!(load.distance_km, batch_load.distance_km);
+                assert!(jitter::score_within_jitter(
+                    load.score.value(),
+                    batch_load.score.value()
+                ));
+            }
+        }
+
+        let invalid_users = [(
+            Some(Location {
+                latitude: f32::NAN,
+                longitude: 0.0,
+            }),
+            None,
+        )];
+        assert!(matches!(
+            compute_loads_batch(&invalid_users, &logicals, &status_file),
+            Err(Error::InvalidUserLocation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compute_loads_blended() {
+        let logicals = (0..3)
+            .map(|index| Logical {
+                exit_country: create_dummy_country(),
+                ..make_logical(index)
+            })
+            .collect::<Vec<_>>();
+        let status_file =
+            make_status_file(&[(3, 10, 0.25), (3, 20, 0.25), (3, 30, 0.25)]);
+        let user_country = Some(create_dummy_country());
+
+        let blended = |historical_partial_scores: &[f32], weight| {
+            let mut loads = vec![Load::default(); logicals.len()];
+            compute_loads_blended(
+                &mut loads,
+                &logicals,
+                &status_file,
+                historical_partial_scores,
+                weight,
+                &None,
+                &user_country,
+            )
+            .map(|_| loads)
+        };
+        let assert_scores = |loads: &[Load], expected: [f64; 3]| {
+            for (load, expected) in std::iter::zip(loads, expected) {
+                assert!(jitter::score_within_jitter(
+                    load.score.value(),
+                    expected
+                ));
+            }
+        };
+
+        // Out of range and non-finite historical scores are handled
+        let loads = blended(&[0.75, f32::NAN, 2.0], 0.5)
+            .expect("Failed to compute loads");
+        assert_scores(&loads, [0.5, 0.25, 0.625]);
+        // Only the partial score is blended
+        assert_eq!(loads[0].load, 10);
+
+        let loads = blended(&[0.75, 0.75, 0.75], f64::NAN)
+            .expect("Failed to compute loads");
+        assert_scores(&loads, [0.25, 0.25, 0.25]);
+        let loads =
+            blended(&[0.75, 0.75, 0.75], 3.0).expect("Failed to compute loads");
+        assert_scores(&loads, [0.75, 0.75, 0.75]);
+
+        // Without historical scores, the loads are the live ones
+        let loads = blended(&[], 0.5).expect("Failed to compute loads");
+        assert_scores(&loads, [0.25, 0.25, 0.25]);
+
+        assert!(matches!(
+            blended(&[0.75], 0.5),
+            Err(Error::HistoricalLengthNotConsistent {
+                servers: 3,
+                historical: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_compute_loads_with_country_override() {
+        let logicals = (0..2)
+            .map(|index| Logical {
+                exit_country: create_dummy_country(),
+                ..make_logical(index)
+            })
+            .collect::<Vec<_>>();
+        let status_file = make_status_file(&[(3, 10, 0.25), (3, 20, 0.25)]);
+        let other_country =
+            CountryCode::try_from(b"FR").expect("Invalid country code");
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads_with_country_override(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &HashMap::from([(1, other_country), (7, other_country)]),
+            &None,
+            &Some(create_dummy_country()),
+        )
+        .expect("Failed to compute loads");
+
+        // Only the overridden server gets the cross-country penalty
+        assert!(!loads[0].is_penalized());
+        assert!(loads[1].is_penalized());
+        assert_eq!(loads[1].load, 20);
+    }
+
+    #[test]
+    fn test_project_server() {
+        let logical = |index| Logical {
+            exit_country: create_dummy_country(),
+            ..make_logical(index)
+        };
+        let logicals = [logical(0), logical(1), logical(2)];
+        let status_file =
+            make_status_file(&[(3, 10, 0.25), (3, 20, 0.5), (3, 30, 0.75)]);
+        let user_country = Some(create_dummy_country());
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads_with_jitter(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &None,
+            &user_country,
+            &mut || 0.0,
+        )
+        .expect("Failed to compute loads");
+
+        let project = |existing_loads: &[Load], partial_score| {
+            project_server(
+                &logical(3),
+                &ServerStatus {
+                    status: STATUS_ENABLED | STATUS_VISIBLE,
+                    load: 40,
+                    partial_score,
+                    ..Default::default()
+                },
+                existing_loads,
+                &None,
+                &user_country,
+                &ScoringConfig::default(),
+            )
+            .expect("Failed to project the server")
+        };
+
+        let (projected, rank) = project(&loads, 0.6);
+        assert_eq!(projected.load, 40);
+        assert_eq!(projected.score.value(), 0.6_f32 as f64);
+        assert_eq!(rank, 2);
+
+        assert_eq!(project(&loads, 0.1).1, 0);
+        assert_eq!(project(&loads, 0.9).1, 3);
+        // Ranks after the existing servers with the same score
+        assert_eq!(project(&loads, 0.5).1, 2);
+
+        // The existing loads don't need to be sorted
+        loads.reverse();
+        assert_eq!(project(&loads, 0.6).1, 2);
+    }
+
+    #[test]
+    fn test_compute_loads_with_default() {
+        let logicals = [0, 1, 5].map(make_logical).to_vec();
+        let status_file = make_status_file(&[
+            (3, 10, 0.0),
+            (3, 255, 0.0), // Invalid load
+        ]);
+        let compute = |default| {
+            let mut loads = vec![Load::default(); logicals.len()];
+            compute_loads_with_default(
+                &mut loads,
+                &logicals,
+                &status_file,
+                &None,
+                &None,
+                default,
+            )
+            .map(|()| loads)
+        };
+
+        let loads = compute(ServerStatus {
+            status: STATUS_ENABLED | STATUS_VISIBLE,
+            load: 50,
+            ..Default::default()
+        })
+        .expect("Failed to compute loads");
+        assert_eq!(loads[0].load, 10);
+        for load in &loads[1..] {
+            assert!(load.is_enabled && load.is_visible);
+            assert_eq!(load.load, 50);
+        }
+
+        let loads =
+            compute(ServerStatus::default()).expect("Failed to compute loads");
+        assert!(loads[0].is_enabled);
+        assert!(!loads[1].is_enabled && !loads[2].is_enabled);
+
+        assert!(matches!(
+            compute(ServerStatus {
+                load: 101,
+                ..Default::default()
+            }),
+            Err(Error::InvalidDefaultStatus(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_server_location_entry_points() {
+        let logical = Logical {
+            exit_location: Location {
+                latitude: f32::NAN,
+                longitude: 2.3522,
+            },
+            ..Default::default()
+        };
+        let user_location = Some(Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        });
+        let is_disabled = |load: &Load| {
+            !load.is_enabled
+                && load.distance_km == 0.0
+                && load.score.value() >= UNAVAILABLE_PENALTY
+                && load.score.value().is_finite()
+        };
+
+        let score = score_at_load(
+            &logical,
+            &user_location,
+            &None,
+            10,
+            0.5,
+            &ScoringConfig::default(),
+        )
+        .expect("Failed to score the server");
+        assert!(score.is_finite() && score >= UNAVAILABLE_PENALTY);
+
+        let status = ServerStatus {
+            status: STATUS_ENABLED | STATUS_VISIBLE,
+            load: 10,
+            ..Default::default()
+        };
+        let (projected, _) = project_server(
+            &logical,
+            &status,
+            &[],
+            &user_location,
+            &None,
+            &ScoringConfig::default(),
+        )
+        .expect("Failed to project the server");
+        assert!(is_disabled(&projected));
+
+        let status_file = make_status_file(&[(3, 10, 0.0)]);
+        let scorer = crate::Scorer::new(
+            std::slice::from_ref(&logical),
+            Parser::try_from(&status_file[..]).expect("Invalid status file"),
+            user_location.clone(),
+            None,
+            ScoringConfig::default(),
+        )
+        .expect("Failed to create the scorer");
+        assert!(is_disabled(&scorer.score(0)));
+
+        let mut scores = [0.0];
+        compute_loads_columnar(
+            std::slice::from_ref(&logical),
+            &status_file,
+            &user_location,
+            &None,
+            &ScoringConfig::default(),
+            &mut scores,
+            &mut [0],
+            &mut [0],
+        )
+        .expect("Failed to compute loads");
+        assert!(scores[0].is_finite() && scores[0] >= UNAVAILABLE_PENALTY);
+    }
+
+    #[test]
+    fn test_compute_loads_invalid_server_location() {
+        let logical = |index, latitude| Logical {
+            exit_location: Location {
+                latitude,
+                longitude: 2.3522,
+            },
+            entry_location: Location {
+                latitude: 48.8566,
+                longitude: 2.3522,
+            },
+            ..make_logical(index)
+        };
+        let logicals = [
+            logical(0, 48.8566),
+            logical(1, f32::NAN),
+            logical(2, 95.0),
+            logical(3, 48.8566),
+        ];
+        let status_file = make_status_file(&[
+            (3, 10, 0.0),
+            (3, 20, 0.0),
+            (3, 30, 0.0),
+            (3, 40, 0.0),
+        ]);
+        let user_location = Some(Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        });
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        let stats = compute_loads_with_stats(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &user_location,
+            &Some(create_dummy_country()),
+        )
+        .expect("Failed to compute loads");
+
+        for load in &loads {
+            assert!(load.score.value().is_finite());
+            assert!(load.distance_km.is_finite());
+        }
+        assert!(loads[0].is_enabled && loads[3].is_enabled);
+        assert!(!loads[1].is_enabled && !loads[2].is_enabled);
+        assert!(loads[1].score.value() >= UNAVAILABLE_PENALTY);
+
+        assert_eq!(
+            stats
+                .defaulted()
+                .iter()
+                .map(|server| (server.logical_index, server.reason.as_str()))
+                .collect::<Vec<_>>(),
+            [(1, INVALID_SERVER_LOCATION), (2, INVALID_SERVER_LOCATION)]
+        );
+    }
+
+    #[test]
+    fn test_compute_loads_with_distances() {
+        let paris = Location {
+            latitude: 48.8566,
+            longitude: 2.3522,
+        };
+        let london = Location {
+            latitude: 51.5074,
+            longitude: -0.1278,
+        };
+        let logicals = vec![
+            Logical {
+                exit_location: paris.clone(),
+                entry_location: paris.clone(),
+                ..Default::default()
+            };
+            2
+        ];
+        let status_file = [1_u8, 0_u8, 0_u8, 0_u8];
+        let distance =
+            compute_user_travel_distance(&paris, &paris, &Some(london.clone()));
+
+        let mut expected = vec![Load::default(); logicals.len()];
+        compute_loads_with_jitter(
+            &mut expected,
+            &logicals,
+            &status_file,
+            &Some(london),
+            &None,
+            &mut || 0.0,
+        )
+        .expect("Failed to compute loads");
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads_with_distances(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &[distance, 10_000.0],
+            &None,
+            &ScoringConfig::default(),
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(loads[0].distance_km, expected[0].distance_km);
+        assert_eq!(loads[1].distance_km, 10_000.0);
+        // The scores differ by at most the jitter.
+        assert!(
+            (loads[0].score.value() - expected[0].score.value()).abs() < 0.01
+        );
+        assert!(loads[1].score > loads[0].score);
+
+        let error = compute_loads_with_distances(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &[distance],
+            &None,
+            &ScoringConfig::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::DistancesLengthNotConsistent {
+                servers: 2,
+                distances: 1
+            }
+        ));
+
+        for invalid in [f64::NAN, f64::INFINITY, -1.0] {
+            let error = compute_loads_with_distances(
+                &mut loads,
+                &logicals,
+                &status_file,
+                &[distance, invalid],
+                &None,
+                &ScoringConfig::default(),
+            )
+            .unwrap_err();
+            assert!(matches!(error, Error::InvalidDistance { index: 1, .. }));
+        }
+
+        // The configuration is applied
+        compute_loads_with_distances(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &[distance, 10_000.0],
+            &None,
+            &ScoringConfig {
+                final_score_clamp: Some((0.0, 0.1)),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to compute loads");
+        assert!(loads.iter().all(|load| load.score.value() <= 0.1));
+    }
+
+    #[test]
+    fn test_compute_loads_with_max_bandwidths() {
+        let paris = Location {
+            latitude: 48.8566,
+            longitude: 2.3522,
+        };
+        let logicals = (0..3)
+            .map(|index| Logical {
+                exit_location: paris.clone(),
+                entry_location: paris.clone(),
+                ..make_logical(index)
+            })
+            .collect::<Vec<_>>();
+        let status_file =
+            make_status_file(&[(3, 20, 0.5), (3, 20, 0.5), (3, 20, 0.5)]);
+        let user_location = Some(paris);
+        let config = ScoringConfig {
+            jitter: jitter::JitterConfig {
+                low: 0.0,
+                high: 0.0,
+            },
+            ..Default::default()
+        };
+
+        let mut expected = vec![Load::default(); logicals.len()];
+        compute_loads_with_config(
+            &mut expected,
+            &logicals,
+            &status_file,
+            &user_location,
+            &None,
+            &config,
+        )
+        .expect("Failed to compute loads");
+
+        // The user is next to the servers, so only a capacity below the
+        // bandwidth estimated from the distance changes the score, once it
+        // is worse than the partial score of 0.5.
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads_with_max_bandwidths(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &user_location,
+            &None,
+            &config,
+            &[None, Some(2000.0), Some(1e9)],
+        )
+        .expect("Failed to compute loads");
+        assert_eq!(loads[0], expected[0]);
+        assert!(loads[1].score > expected[1].score);
+        assert_eq!(loads[2], expected[2]);
+
+        let error = compute_loads_with_max_bandwidths(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &user_location,
+            &None,
+            &config,
+            &[None],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::MaxBandwidthsLengthNotConsistent {
+                servers: 3,
+                max_bandwidths: 1
+            }
+        ));
+
+        for invalid in [f64::NAN, -1.0] {
+            let error = compute_loads_with_max_bandwidths(
+                &mut loads,
+                &logicals,
+                &status_file,
+                &user_location,
+                &None,
+                &config,
+                &[None, Some(invalid), None],
+            )
+            .unwrap_err();
+            assert!(matches!(
+                error,
+                Error::InvalidMaxBandwidth { index: 1, .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_compute_loads_with_stats() {
+        let logicals = [0, 1, 2, 7].map(make_logical);
+        let status_file = make_status_file(&[
+            (3, 101, 0.0), // Invalid load
+            (3, 42, 0.0),
+            (3, 200, 0.0), // Invalid load
+        ]);
+        let mut loads = vec![Load::default(); logicals.len()];
+
+        let parse_errors = compute_loads_with_stats(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &None,
+            &None,
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(parse_errors.total(), 2);
+        assert_eq!(
+            parse_errors.counts().collect::<Vec<_>>(),
+            vec![("Server load must be between 0 and 100", 2)]
+        );
+        assert_eq!(loads[1].load, 42);
+
+        let defaulted_server =
+            |logical_index, status_index, reason: &str| DefaultedServer {
+                logical_index,
+                status_index: ServerIndex(status_index),
+                reason: reason.to_owned(),
+            };
+        assert_eq!(
+            parse_errors.defaulted(),
+            [
+                defaulted_server(0, 0, "Server load must be between 0 and 100"),
+                defaulted_server(2, 2, "Server load must be between 0 and 100"),
+                defaulted_server(3, 7, STATUS_INDEX_OUT_OF_RANGE),
+            ]
+        );
     }
 
     #[test]
@@ -144,28 +2314,28 @@ mod tests {
         let servers = vec![
             Logical {
                 status_reference: StatusReference {
-                    index: 0,
+                    index: ServerIndex(0),
                     ..Default::default()
                 },
                 ..Default::default()
             },
             Logical {
                 status_reference: StatusReference {
-                    index: 1,
+                    index: ServerIndex(1),
                     ..Default::default()
                 },
                 ..Default::default()
             },
             Logical {
                 status_reference: StatusReference {
-                    index: 2,
+                    index: ServerIndex(2),
                     ..Default::default()
                 },
                 ..Default::default()
             },
             Logical {
                 status_reference: StatusReference {
-                    index: 3,
+                    index: ServerIndex(3),
                     ..Default::default()
                 },
                 ..Default::default()
@@ -204,4 +2374,594 @@ mod tests {
         assert!(loads[3].is_enabled);
         assert!(loads[3].is_visible);
     }
+
+    #[test]
+    fn test_compute_loads_status_byte() {
+        let servers = make_logicals(1);
+        let mut loads = vec![Load::default(); servers.len()];
+
+        // Enabled, visible, bit 4
+        let status_file = make_status_file(&[(19, 0, 0.0)]);
+
+        compute_loads(
+            &mut loads,
+            &servers,
+            &status_file,
+            &Some(create_dummy_location()),
+            &Some(create_dummy_country()),
+        )
+        .expect("Failed to compute loads");
+
+        // The unknown bits are kept in the raw status byte.
+        assert_eq!(loads[0].status, 19);
+        assert!(loads[0].is_enabled);
+        assert!(loads[0].is_visible);
+    }
+
+    #[test]
+    fn test_compute_loads_display_fraction() {
+        let servers = make_logicals(2);
+        let mut loads = vec![Load::default(); servers.len()];
+
+        let status_file = make_status_file(&[
+            (3, 0, 0.25), // Enabled, visible
+            (0, 0, 0.25), // Disabled, hidden
+        ]);
+
+        compute_loads_with_config(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &None,
+            &ScoringConfig {
+                compute_display_fraction: true,
+                ..Default::default()
+            },
+        )
+        .expect("Failed to compute loads");
+
+        #[cfg(not(feature = "jitter"))]
+        assert_eq!(loads[0].display_fraction, 0.75);
+        assert!((loads[0].display_fraction - 0.75).abs() <= 0.005);
+        assert_eq!(loads[1].display_fraction, 0.0);
+
+        // The fraction is not computed by default
+        compute_loads(&mut loads, &servers, &status_file, &None, &None)
+            .expect("Failed to compute loads");
+
+        assert_eq!(loads[0].display_fraction, 0.0);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn test_compute_loads_debug_fields() {
+        let servers = vec![Logical::default()];
+        let mut loads = vec![Load::default(); servers.len()];
+
+        let status_file = make_status_file(&[(3, 0, 0.5)]);
+
+        compute_loads(
+            &mut loads,
+            &servers,
+            &status_file,
+            &Some(create_dummy_location()),
+            &Some(create_dummy_country()),
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(loads[0].debug.partial_score, 0.5);
+    }
+
+    #[test]
+    fn test_compute_loads_with_jitter() {
+        let servers = vec![Logical::default(); 3];
+        let mut loads = vec![Load::default(); servers.len()];
+
+        let status_file = make_status_file(&[(3, 0, 0.5)]);
+
+        let mut calls = 0;
+        let mut jitter = || {
+            calls += 1;
+            0.001 * calls as f64
+        };
+
+        compute_loads_with_jitter(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &Some(create_dummy_country()),
+            &mut jitter,
+        )
+        .expect("Failed to compute loads");
+
+        // The generator keeps its state between calls
+        compute_loads_with_jitter(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &Some(create_dummy_country()),
+            &mut jitter,
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(calls, 6);
+        for (i, load) in loads.iter().enumerate() {
+            let expected = 1.0 + 0.5 + 0.001 * (4 + i) as f64;
+            assert!((load.score.value() - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_compute_loads_with_asymmetric_jitter() {
+        let servers = vec![Logical::default(); 100];
+        let mut loads = vec![Load::default(); servers.len()];
+
+        let status_file = make_status_file(&[(3, 0, 0.5)]);
+
+        let config = |low, high| ScoringConfig {
+            jitter: jitter::JitterConfig { low, high },
+            ..Default::default()
+        };
+
+        // The jitter only ever worsens the scores
+        compute_loads_with_config(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &Some(create_dummy_country()),
+            &config(-0.5, 0.0),
+        )
+        .expect("Failed to compute loads");
+        for load in &loads {
+            assert!(load.score.value() >= 1.0 + 0.5);
+        }
+
+        assert!(matches!(
+            compute_loads_with_config(
+                &mut loads,
+                &servers,
+                &status_file,
+                &None,
+                &Some(create_dummy_country()),
+                &config(0.5, -0.5),
+            ),
+            Err(Error::InvalidJitterRange { .. })
+        ));
+
+        assert!(matches!(
+            compute_loads_with_config(
+                &mut loads,
+                &servers,
+                &status_file,
+                &None,
+                &Some(create_dummy_country()),
+                &ScoringConfig {
+                    preferred_country_bonus: -0.5,
+                    ..Default::default()
+                },
+            ),
+            Err(Error::InvalidPreferredCountryBonus { .. })
+        ));
+
+        assert!(matches!(
+            compute_loads_with_config(
+                &mut loads,
+                &servers,
+                &status_file,
+                &None,
+                &Some(create_dummy_country()),
+                &ScoringConfig {
+                    country_weights: [(create_dummy_country(), f64::NAN)]
+                        .into(),
+                    ..Default::default()
+                },
+            ),
+            Err(Error::InvalidCountryWeight { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compute_loads_columnar() {
+        let servers = make_logicals(3);
+
+        let status_file = make_status_file(&[
+            (1, 10, 0.0), // Enabled, not visible
+            (3, 20, 0.0), // Enabled, visible
+            (7, 30, 0.0), // Autoconnectable
+        ]);
+
+        // Without jitter, so that the scores are reproducible
+        let config = ScoringConfig {
+            jitter: jitter::JitterConfig {
+                low: 0.0,
+                high: 0.0,
+            },
+            final_score_clamp: Some((0.0, 0.5)),
+            ..Default::default()
+        };
+
+        let mut scores = vec![0.0; servers.len()];
+        let mut server_loads = vec![0_u8; servers.len()];
+        let mut flags = vec![0_u8; servers.len()];
+        compute_loads_columnar(
+            &servers,
+            &status_file,
+            &Some(create_dummy_location()),
+            &Some(create_dummy_country()),
+            &config,
+            &mut scores,
+            &mut server_loads,
+            &mut flags,
+        )
+        .expect("Failed to compute loads");
+
+        let mut loads = vec![Load::default(); servers.len()];
+        compute_loads_with_config(
+            &mut loads,
+            &servers,
+            &status_file,
+            &Some(create_dummy_location()),
+            &Some(create_dummy_country()),
+            &config,
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(server_loads, [10, 20, 30]);
+        assert_eq!(flags, [1, 3, 7]);
+        assert_eq!(
+            scores,
+            loads
+                .iter()
+                .map(|load| load.score.value())
+                .collect::<Vec<_>>()
+        );
+        // The configuration is applied
+        assert!(scores.iter().all(|&score| score <= 0.5));
+
+        let error = compute_loads_columnar(
+            &servers,
+            &status_file,
+            &None,
+            &None,
+            &ScoringConfig::default(),
+            &mut [0.0; 3],
+            &mut [0_u8; 3],
+            &mut [0_u8; 2],
+        )
+        .unwrap_err();
+
+        match error {
+            Error::LengthsNotConsistent { servers, loads } => {
+                assert_eq!(servers, 3);
+                assert_eq!(loads, 2);
+            }
+            _ => panic!("Expected LengthsNotConsistent error"),
+        }
+    }
+
+    #[test]
+    fn test_compute_loads_dual() {
+        let paris = Location {
+            latitude: 48.8566,
+            longitude: 2.3522,
+        };
+        let geneva = Location {
+            latitude: 46.2044,
+            longitude: 6.1432,
+        };
+        let london = Location {
+            latitude: 51.5074,
+            longitude: -0.1278,
+        };
+
+        // A secure core server, entering in Geneva and exiting in Paris
+        let servers = vec![Logical {
+            entry_location: geneva.clone(),
+            exit_location: paris.clone(),
+            ..Default::default()
+        }];
+
+        let status_file = make_status_file(&[(3, 10, 0.0)]);
+
+        let mut loads_legacy = vec![Load::default(); servers.len()];
+        let mut loads_new = vec![Load::default(); servers.len()];
+        compute_loads_dual(
+            &mut loads_legacy,
+            &mut loads_new,
+            &servers,
+            &status_file,
+            &Some(london.clone()),
+            &None,
+            &ScoringConfig::default(),
+        )
+        .expect("Failed to compute loads");
+
+        let distance = |mode| {
+            let (client_leg, internal_leg) =
+                crate::compute_score::compute_travel_distance_legs_with_mode(
+                    &paris, &geneva, &london, mode,
+                );
+            client_leg + internal_leg
+        };
+        assert_eq!(loads_legacy[0].distance_km, distance(DistanceMode::Legacy));
+        assert_eq!(
+            loads_new[0].distance_km,
+            distance(DistanceMode::EntryFirst)
+        );
+        assert_eq!(loads_legacy[0].load, 10);
+        assert_eq!(loads_new[0].load, 10);
+
+        // Both loads share the same jitter, so a server whose distance is
+        // the same in both modes gets the same score.
+        let servers = vec![Logical {
+            entry_location: paris.clone(),
+            exit_location: paris.clone(),
+            ..Default::default()
+        }];
+        compute_loads_dual(
+            &mut loads_legacy,
+            &mut loads_new,
+            &servers,
+            &status_file,
+            &Some(london.clone()),
+            &None,
+            &ScoringConfig::default(),
+        )
+        .expect("Failed to compute loads");
+        assert_eq!(loads_legacy[0].distance_km, loads_new[0].distance_km);
+        assert_eq!(loads_legacy[0].score, loads_new[0].score);
+
+        let error = compute_loads_dual(
+            &mut loads_legacy,
+            &mut [],
+            &servers,
+            &status_file,
+            &None,
+            &None,
+            &ScoringConfig::default(),
+        )
+        .unwrap_err();
+
+        match error {
+            Error::LengthsNotConsistent { servers, loads } => {
+                assert_eq!(servers, 1);
+                assert_eq!(loads, 0);
+            }
+            _ => panic!("Expected LengthsNotConsistent error"),
+        }
+    }
+
+    #[test]
+    fn test_compute_loads_filtered() {
+        let servers = make_logicals(4);
+
+        let status_file = make_status_file(&[
+            (3, 10, 0.0),
+            (3, 20, 0.0),
+            (3, 30, 0.0),
+            (3, 40, 0.0),
+        ]);
+
+        let mut loads = vec![Load::default(); 10];
+        let indices = compute_loads_filtered(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &None,
+            &ScoringConfig::default(),
+            |logical| logical.status_reference.index.0 % 2 == 1,
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(indices, [1, 3]);
+        assert_eq!(
+            loads.iter().map(|load| load.load).collect::<Vec<_>>(),
+            [20, 40]
+        );
+
+        let indices = compute_loads_filtered(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &None,
+            &ScoringConfig::default(),
+            |_| false,
+        )
+        .expect("Failed to compute loads");
+
+        assert!(indices.is_empty());
+        assert!(loads.is_empty());
+    }
+
+    #[test]
+    fn test_compute_loads_status_file_penalty() {
+        let mut servers = make_logicals(1);
+        servers[0].status_reference.penalty = 2.0;
+        let mut loads = vec![Load::default(); servers.len()];
+
+        let mut status_file = vec![2_u8, 0_u8, 0_u8, 0_u8];
+        status_file.extend_from_slice(&[3_u8, 0_u8]); // Enabled, visible
+        status_file.extend_from_slice(&0.5_f32.to_le_bytes());
+        status_file.extend_from_slice(&5.0_f32.to_le_bytes()); // Penalty
+        status_file.extend_from_slice(&0_u16.to_le_bytes()); // Load
+
+        let mut compute = |precedence| {
+            compute_loads_with_config(
+                &mut loads,
+                &servers,
+                &status_file,
+                &None,
+                &None,
+                &ScoringConfig {
+                    status_penalty_precedence: precedence,
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to compute loads");
+
+            loads[0].penalty
+        };
+
+        assert_eq!(compute(StatusPenaltyPrecedence::StatusFile), 5.0);
+        assert_eq!(compute(StatusPenaltyPrecedence::Logical), 2.0);
+        assert_eq!(compute(StatusPenaltyPrecedence::Sum), 7.0);
+    }
+
+    #[test]
+    fn test_compute_loads_distance() {
+        let paris = Location {
+            latitude: 48.8566,
+            longitude: 2.3522,
+        };
+        let servers = vec![Logical {
+            entry_location: paris.clone(),
+            exit_location: paris.clone(),
+            ..Default::default()
+        }];
+
+        let mut loads = vec![Load::default(); servers.len()];
+        let status_file = make_status_file(&[(3, 0, 0.0)]);
+
+        let user_location = Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        };
+        compute_loads(
+            &mut loads,
+            &servers,
+            &status_file,
+            &Some(user_location.clone()),
+            &Some(create_dummy_country()),
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(
+            loads[0].distance_km,
+            compute_user_travel_distance(&paris, &paris, &Some(user_location))
+        );
+
+        #[cfg(feature = "debug")]
+        {
+            assert_eq!(loads[0].debug.client_leg_km, loads[0].distance_km);
+            assert_eq!(loads[0].debug.internal_leg_km, 0.0);
+        }
+        assert!((loads[0].distance_km - 343.0).abs() < 1.0);
+
+        // Without a user location the distance is unknown and reported as 0.
+        compute_loads(
+            &mut loads,
+            &servers,
+            &status_file,
+            &None,
+            &Some(create_dummy_country()),
+        )
+        .expect("Failed to compute loads");
+
+        assert_eq!(loads[0].distance_km, 0.0);
+    }
+
+    #[test_log::test]
+    fn test_compute_loads_internal_leg_weight() {
+        // A secure core server, entering in Geneva and exiting in Paris
+        let servers = vec![Logical {
+            entry_location: Location {
+                latitude: 46.2044,
+                longitude: 6.1432,
+            },
+            exit_location: Location {
+                latitude: 48.8566,
+                longitude: 2.3522,
+            },
+            ..Default::default()
+        }];
+
+        let status_file = make_status_file(&[(3, 0, 0.0)]);
+        let user_location = Some(Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        });
+
+        let compute = |internal_leg_weight| {
+            let mut loads = vec![Load::default(); servers.len()];
+            compute_loads_impl(
+                &mut loads,
+                &servers,
+                &status_file,
+                &user_location,
+                &Some(create_dummy_country()),
+                &ScoringConfig {
+                    internal_leg_weight,
+                    ..Default::default()
+                },
+                &mut || 0.0,
+            )
+            .expect("Failed to compute loads");
+            loads.remove(0)
+        };
+
+        let full = compute(1.0);
+        let half = compute(0.5);
+        let none = compute(0.0);
+
+        assert!(half.score < full.score);
+        assert!(none.score < half.score);
+
+        // The displayed distance is not weighted
+        assert_eq!(half.distance_km, full.distance_km);
+        assert_eq!(none.distance_km, full.distance_km);
+    }
+
+    #[test_log::test]
+    fn test_compute_loads_country_centroid_fallback() {
+        let server = |latitude, longitude| Logical {
+            entry_location: Location {
+                latitude,
+                longitude,
+            },
+            exit_location: Location {
+                latitude,
+                longitude,
+            },
+            ..Default::default()
+        };
+        // Bern and Geneva
+        let servers = vec![server(46.9480, 7.4474), server(46.2044, 6.1432)];
+
+        let status_file = make_status_file(&[(3, 0, 0.0), (3, 0, 0.0)]);
+        let user_country = Some(CountryCode::try_from(b"CH").unwrap());
+
+        let compute = |country_centroid_fallback| {
+            let mut loads = vec![Load::default(); servers.len()];
+            compute_loads_impl(
+                &mut loads,
+                &servers,
+                &status_file,
+                &None,
+                &user_country,
+                &ScoringConfig {
+                    country_centroid_fallback,
+                    ..Default::default()
+                },
+                &mut || 0.0,
+            )
+            .expect("Failed to compute loads");
+            loads
+        };
+
+        // Without the fallback the servers can't be told apart by distance
+        let loads = compute(false);
+        assert_eq!(loads[0].distance_km, 0.0);
+        assert_eq!(loads[0].score, loads[1].score);
+
+        // Bern is closer to the center of Switzerland than Geneva
+        let loads = compute(true);
+        assert!(loads[0].distance_km < loads[1].distance_km);
+        assert!(loads[0].score < loads[1].score);
+    }
 }
@@ -3,21 +3,29 @@
 // -----------------------------------------------------------------------------
 use super::coord::Coord;
 use super::country_code::CountryCode;
-#[cfg(feature = "debug")]
-use super::load::LoadDebugFields;
 use super::location::Location;
+use super::logical::{Cost, Logical};
+use super::scoring_config::{
+    DistanceMode, ScoringConfig, UnknownCountryPolicy,
+};
+use super::status::ServerStatus;
 // -----------------------------------------------------------------------------
 const PARTIAL_SCORE_CEILING: f64 = 0.99; // = normalize(100.0) = (10000 - 100) / 10000
-const BANDWITH_DISTANCE_FACTOR: f64 = 738_000.0; // Mbps/km
+pub(crate) const BANDWITH_DISTANCE_FACTOR: f64 = 738_000.0; // Mbps/km
 
 pub(crate) const SCORE_NORMALIZATION_FACTOR: f64 = 10_000.0; // Mbps (10 Gbps)
 
+// The client side penalties
+pub(crate) const UNAVAILABLE_PENALTY: f64 = 1000.0; // Disabled or hidden
+pub(crate) const FOREIGN_OR_SATURATED_PENALTY: f64 = 1.0;
+pub(crate) const FOREIGN_EXPENSIVE_PENALTY: f64 = 3.0;
+pub(crate) const OVER_CAPACITY_PENALTY: f64 = 1.0; // Country over its cap
+
 // The server load jitter range is 100 Mbps. It oscillates between -50 and +50 Mbps.
 // The jitter is applied after the load is normalized and so we must also
 // normalize it. As the jitter is an offset, and not an absolute value,
 // normalization is done by dividing the jitter by the SCORE_NORMALIZATION_FACTOR
 // and negating it.
-#[cfg(feature = "jitter")]
 pub(crate) const NORMALIZED_JITTER_RANGE: f64 =
     -(100.0 / SCORE_NORMALIZATION_FACTOR); // (100 Mbps)  normalized to 0.01
 
@@ -25,17 +33,19 @@ pub const STATUS_ENABLED: u8 = 1 << 0;
 pub const STATUS_VISIBLE: u8 = 1 << 1;
 pub const STATUS_AUTOCONNECTABLE: u8 = 1 << 2;
 
-pub struct ComputeScoreServerParams<'a> {
+/// The scoring inputs of a server, as resolved by `compute_loads` from its
+/// logical and status.
+pub struct ComputeScoreServerParams {
     pub status_penalty: f64,
     pub status_cost: u8,
     pub country: CountryCode,
     pub partial_score: f64,
     pub status: u8,
-    pub exit_location: &'a Location, // 0 = Lat, 1 = Long
-    pub entry_location: &'a Location,
+    pub travel_distance_km: f64,
     pub normalized_jitter: f64,
-    #[cfg(feature = "debug")]
-    pub debug: &'a mut LoadDebugFields,
+    // The measured bandwidth capacity of the server, when known. It caps the
+    // bandwidth estimated from the travel distance.
+    pub max_bandwidth_mbps: Option<f64>,
 }
 
 pub(crate) fn normalize(score: f64) -> f64 {
@@ -48,53 +58,318 @@ fn compute_distance_between(a: &Location, b: &Location) -> f64 {
     )
 }
 
-// Depending on whether we are using legacy mode or not,
-// the travel distance is computed differently.
+/// Returns whether the library was built with the `legacy` feature, i.e.
+/// whether the client leg of the travel distance goes to the server exit
+/// instead of the server entry.
+pub const fn legacy_enabled() -> bool {
+    cfg!(feature = "legacy")
+}
+
+/// Returns the `(client_leg_km, internal_leg_km)` legs of the travel distance.
+pub fn compute_travel_distance_legs(
+    server_exit_location: &Location,
+    server_entry_location: &Location,
+    client_position: &Location,
+) -> (f64, f64) {
+    compute_travel_distance_legs_with_mode(
+        server_exit_location,
+        server_entry_location,
+        client_position,
+        DistanceMode::default(),
+    )
+}
+
+// Same as `compute_travel_distance_legs`, with the given distance mode
+// instead of the default one.
+//
+// The travel distance is made of two legs, the client leg (from the client to
+// the server) and the internal leg (from the server entry to the server exit,
+// which is only non-zero for secure core servers).
 //
-// In legacy mode, we calculate the distance from the client to the server as:
+// In legacy mode, we calculate the client leg as:
 //   distance(client -> server_exit)
-// In non-legacy mode, we calculate the distance from the client to the server as:
+// In the other modes, we calculate the client leg as:
 //   distance(client -> server_entry)
+// except for the nearest endpoint mode, which takes the closer of the server
+// entry and exit. The entry only mode drops the internal leg.
+pub(crate) fn compute_travel_distance_legs_with_mode(
+    server_exit_location: &Location,
+    server_entry_location: &Location,
+    client_position: &Location,
+    mode: DistanceMode,
+) -> (f64, f64) {
+    let internal_leg = || {
+        compute_distance_between(server_entry_location, server_exit_location)
+    };
 
-#[cfg(feature = "legacy")]
+    match mode {
+        DistanceMode::Legacy => (
+            compute_distance_between(client_position, server_exit_location),
+            internal_leg(),
+        ),
+        DistanceMode::EntryFirst => (
+            compute_distance_between(client_position, server_entry_location),
+            internal_leg(),
+        ),
+        DistanceMode::EntryOnly => (
+            compute_distance_between(client_position, server_entry_location),
+            0.0,
+        ),
+        DistanceMode::NearestEndpoint => (
+            f64::min(
+                compute_distance_between(
+                    client_position,
+                    server_entry_location,
+                ),
+                compute_distance_between(client_position, server_exit_location),
+            ),
+            internal_leg(),
+        ),
+    }
+}
+
+/// Computes the distance in km from the client to the server exit, going
+/// through the server entry.
+///
+/// It is `compute_travel_distance_legacy` when the library is built with the
+/// `legacy` feature, and `compute_travel_distance_modern` otherwise.
 pub fn compute_travel_distance(
     server_exit_location: &Location,
     server_entry_location: &Location,
     client_position: &Location,
 ) -> f64 {
-    compute_distance_between(client_position, server_exit_location)
-        + compute_distance_between(server_entry_location, server_exit_location)
+    if legacy_enabled() {
+        compute_travel_distance_legacy(
+            server_exit_location,
+            server_entry_location,
+            client_position,
+        )
+    } else {
+        compute_travel_distance_modern(
+            server_exit_location,
+            server_entry_location,
+            client_position,
+        )
+    }
 }
 
-#[cfg(not(feature = "legacy"))]
-pub fn compute_travel_distance(
+/// Computes the travel distance in km as the `legacy` feature does, whether
+/// the library is built with it or not: from the client to the server exit,
+/// plus from the server entry to the server exit.
+pub fn compute_travel_distance_legacy(
     server_exit_location: &Location,
     server_entry_location: &Location,
     client_position: &Location,
 ) -> f64 {
-    compute_distance_between(client_position, server_entry_location)
-        + compute_distance_between(server_entry_location, server_exit_location)
+    let (client_leg, internal_leg) = compute_travel_distance_legs_with_mode(
+        server_exit_location,
+        server_entry_location,
+        client_position,
+        DistanceMode::Legacy,
+    );
+
+    client_leg + internal_leg
 }
 
-pub(crate) fn compute_distance_score(
+/// Computes the travel distance in km as the library does without the
+/// `legacy` feature, whether it is built with it or not: from the client to
+/// the server entry, plus from the server entry to the server exit.
+pub fn compute_travel_distance_modern(
     server_exit_location: &Location,
     server_entry_location: &Location,
-    client_position: &Option<Location>,
+    client_position: &Location,
 ) -> f64 {
-    let distance_in_km = if let Some(client_position) = client_position {
-        compute_travel_distance(
+    let (client_leg, internal_leg) = compute_travel_distance_legs_with_mode(
+        server_exit_location,
+        server_entry_location,
+        client_position,
+        DistanceMode::EntryFirst,
+    );
+
+    client_leg + internal_leg
+}
+
+// The legs of the travel distance used for scoring. When the client position
+// is unknown both legs are 0.0, which gives every server the same distance
+// score.
+pub(crate) fn compute_user_travel_distance_legs(
+    server_exit_location: &Location,
+    server_entry_location: &Location,
+    client_position: &Option<Location>,
+    mode: DistanceMode,
+) -> (f64, f64) {
+    if let Some(client_position) = client_position {
+        compute_travel_distance_legs_with_mode(
             server_exit_location,
             server_entry_location,
             client_position,
+            mode,
         )
     } else {
-        0.0
-    };
+        (0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn compute_user_travel_distance(
+    server_exit_location: &Location,
+    server_entry_location: &Location,
+    client_position: &Option<Location>,
+) -> f64 {
+    let (client_leg, internal_leg) = compute_user_travel_distance_legs(
+        server_exit_location,
+        server_entry_location,
+        client_position,
+        DistanceMode::default(),
+    );
+
+    client_leg + internal_leg
+}
+
+/// Returns the bandwidth of the server estimated from its travel distance,
+/// in Mbps, which the distance score is computed from.
+///
+/// The travel distance is computed as for scoring, with the
+/// `distance_mode` and `internal_leg_weight` of the configuration.
+pub fn estimated_bandwidth_mbps(
+    server_exit_location: &Location,
+    server_entry_location: &Location,
+    client_position: &Location,
+    config: &ScoringConfig,
+) -> f64 {
+    let (client_leg, internal_leg) = compute_travel_distance_legs_with_mode(
+        server_exit_location,
+        server_entry_location,
+        client_position,
+        config.distance_mode,
+    );
+
+    estimate_bandwidth_from_distance(
+        client_leg + config.internal_leg_weight * internal_leg,
+        None,
+        config,
+    )
+}
 
+fn estimate_bandwidth_from_distance(
+    distance_in_km: f64,
+    max_bandwidth_mbps: Option<f64>,
+    config: &ScoringConfig,
+) -> f64 {
     let proximity_based_bandwidth_estimate =
-        BANDWITH_DISTANCE_FACTOR / f64::max(1.0, distance_in_km);
+        config.bandwidth_model.factor(distance_in_km)
+            / f64::max(1.0, distance_in_km);
 
-    normalize(proximity_based_bandwidth_estimate)
+    // A server can't provide more bandwidth than its capacity, however close
+    match max_bandwidth_mbps {
+        Some(max_bandwidth_mbps) => {
+            f64::min(proximity_based_bandwidth_estimate, max_bandwidth_mbps)
+        }
+        None => proximity_based_bandwidth_estimate,
+    }
+}
+
+pub(crate) fn compute_distance_score_from_distance(
+    distance_in_km: f64,
+    max_bandwidth_mbps: Option<f64>,
+    config: &ScoringConfig,
+) -> f64 {
+    let proximity_based_bandwidth_estimate = estimate_bandwidth_from_distance(
+        distance_in_km,
+        max_bandwidth_mbps,
+        config,
+    );
+
+    match &config.normalize_fn {
+        Some(normalize_fn) => normalize_fn(proximity_based_bandwidth_estimate),
+        None => normalize(proximity_based_bandwidth_estimate),
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn compute_distance_score(
+    server_exit_location: &Location,
+    server_entry_location: &Location,
+    client_position: &Option<Location>,
+) -> f64 {
+    compute_distance_score_from_distance(
+        compute_user_travel_distance(
+            server_exit_location,
+            server_entry_location,
+            client_position,
+        ),
+        None,
+        &ScoringConfig::default(),
+    )
+}
+
+/// Scores each candidate entry of a secure core server for the given exit,
+/// by the distance score of the route from the user through the entry to
+/// the exit. Lower is better.
+///
+/// Only the distance is scored, the loads of the servers are not taken into
+/// account. The internal leg is weighted by `config.internal_leg_weight`, as
+/// when scoring servers.
+pub fn compute_entry_scores(
+    exit: &Location,
+    candidate_entries: &[Location],
+    user: &Location,
+    config: &ScoringConfig,
+) -> Vec<f64> {
+    candidate_entries
+        .iter()
+        .map(|entry| {
+            let travel_distance_km = compute_distance_between(user, entry)
+                + config.internal_leg_weight
+                    * compute_distance_between(entry, exit);
+
+            compute_distance_score_from_distance(
+                travel_distance_km,
+                None,
+                config,
+            )
+        })
+        .collect()
+}
+
+/// Tells which of the client side penalty clauses applied to a server.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PenaltyFlags {
+    // The server is disabled or hidden.
+    #[cfg_attr(feature = "serde", serde(rename = "DisabledOrHidden"))]
+    pub disabled_or_hidden: bool,
+    // The server is in another country than the user.
+    #[cfg_attr(feature = "serde", serde(rename = "CrossCountry"))]
+    pub cross_country: bool,
+    // The server is expensive and in another country than the user.
+    #[cfg_attr(feature = "serde", serde(rename = "Expensive"))]
+    pub expensive: bool,
+    // The partial score of the server reached the ceiling.
+    #[cfg_attr(feature = "serde", serde(rename = "Saturated"))]
+    pub saturated: bool,
+}
+
+/// Explains which client side penalties apply to a server with the given
+/// status, using the default `ScoringConfig`.
+pub fn explain_penalty(
+    logical: &Logical,
+    status: &ServerStatus,
+    user_country: &Option<CountryCode>,
+) -> PenaltyFlags {
+    let (_, flags) = compute_penalty(
+        logical.status_reference.penalty,
+        logical.status_reference.cost,
+        status.partial_score as f64,
+        user_country,
+        logical.exit_country,
+        status.status,
+        &ScoringConfig::default(),
+    );
+
+    flags
 }
 
 pub(crate) fn compute_penalty(
@@ -104,11 +379,15 @@ pub(crate) fn compute_penalty(
     client_country: &Option<CountryCode>,
     server_country: CountryCode,
     server_status: u8,
-) -> f64 {
+    config: &ScoringConfig,
+) -> (f64, PenaltyFlags) {
     let is_in_same_country = if let Some(country) = client_country {
         (*country) == server_country
     } else {
-        true
+        match config.unknown_country_policy {
+            UnknownCountryPolicy::AssumeDomestic => true,
+            UnknownCountryPolicy::AssumeForeign => false,
+        }
     };
 
     //--------------------------------------------------------------------------
@@ -119,65 +398,174 @@ pub(crate) fn compute_penalty(
     //--------------------------------------------------------------------------
     // The client side penalties
     //--------------------------------------------------------------------------
+    let expensive_tier = if config.tiered_cost {
+        Cost::from_byte(status_cost).tier
+    } else {
+        u8::from(status_cost != 0)
+    };
     let server_disabled = (server_status & STATUS_ENABLED) == 0;
     let server_hidden = (server_status & STATUS_VISIBLE) == 0;
-    if server_disabled || server_hidden {
-        penalty += 1000.0;
+    let flags = PenaltyFlags {
+        disabled_or_hidden: server_disabled || server_hidden,
+        cross_country: !is_in_same_country,
+        expensive: (!is_in_same_country) && expensive_tier > 0,
+        saturated: norm_server_available_bandwidth_for_session
+            >= PARTIAL_SCORE_CEILING,
+    };
+
+    if flags.disabled_or_hidden {
+        penalty += UNAVAILABLE_PENALTY;
     }
 
-    if (!is_in_same_country)
-        || norm_server_available_bandwidth_for_session >= PARTIAL_SCORE_CEILING
-    {
-        penalty += 1.0;
+    if flags.cross_country || flags.saturated {
+        penalty += FOREIGN_OR_SATURATED_PENALTY;
     }
 
-    if (!is_in_same_country) && status_cost == 1_u8 {
-        penalty += 3.0;
+    if flags.expensive {
+        penalty += FOREIGN_EXPENSIVE_PENALTY * f64::from(expensive_tier);
     }
 
-    penalty
+    (penalty, flags)
 }
 
-/// Computes the loads from an array of servers and a status file.
-pub fn compute_score(
-    server: ComputeScoreServerParams,
-    user_location: &Option<Location>,
-    user_country: &Option<CountryCode>,
-) -> f64 {
-    let distance_score = compute_distance_score(
-        server.exit_location,
-        server.entry_location,
-        user_location,
-    );
-
-    let capped_score = f64::max(distance_score, server.partial_score);
+// The two components of a score, the final score is their sum.
+pub(crate) struct ScoreBreakdown {
+    // In the range 0.0..=1.0, lower is better.
+    pub base_score: f64,
+    // 0.0 when no penalty applies.
+    pub penalty: f64,
+}
 
-    let base_score = (capped_score + server.normalized_jitter).clamp(0.0, 1.0);
+impl ScoreBreakdown {
+    pub fn score(&self) -> f64 {
+        self.base_score + self.penalty
+    }
 
-    // Additional debug information
-    #[cfg(feature = "debug")]
-    {
-        server.debug.partial_score = server.partial_score;
+    // The fraction to display in a quality bar, 1.0 is the best.
+    pub fn display_fraction(&self) -> f64 {
+        if self.penalty > 0.0 {
+            0.0
+        } else {
+            1.0 - self.base_score
+        }
     }
+}
 
-    let penalty = compute_penalty(
+/// Returns the `(base_score, jitter, penalty)` of a server before the base
+/// score and the jitter are summed and clamped to 0.0..=1.0, for measuring
+/// how often the clamping changes the score.
+///
+/// The base score doesn't include `ScoringConfig::preferred_country_bonus`
+/// and `ScoringConfig::country_weights`, which apply to the clamped score.
+/// `compute_loads` always clamps the score.
+pub fn compute_score_raw(
+    server: ComputeScoreServerParams,
+    user_country: &Option<CountryCode>,
+    config: &ScoringConfig,
+) -> (f64, f64, f64) {
+    let (penalty, _) = compute_penalty(
         server.status_penalty,
         server.status_cost,
         server.partial_score,
         user_country,
         server.country,
         server.status,
+        config,
+    );
+
+    (
+        compute_unclamped_base_score(&server, config),
+        server.normalized_jitter,
+        penalty,
+    )
+}
+
+// The base score of the server, before the jitter is applied.
+fn compute_unclamped_base_score(
+    server: &ComputeScoreServerParams,
+    config: &ScoringConfig,
+) -> f64 {
+    let distance_score = compute_distance_score_from_distance(
+        server.travel_distance_km,
+        server.max_bandwidth_mbps,
+        config,
     );
 
-    base_score + penalty
+    config.cap_strategy.combine(
+        distance_score,
+        if config.ignore_partial_score {
+            0.0
+        } else {
+            server.partial_score
+        },
+    )
+}
+
+// The base score of the server, in the range 0.0..=1.0, with the jitter and
+// the country preferences of the configuration applied. Unlike the penalty,
+// it depends on the travel distance.
+pub(crate) fn compute_base_score(
+    server: &ComputeScoreServerParams,
+    config: &ScoringConfig,
+) -> f64 {
+    let mut base_score = (compute_unclamped_base_score(server, config)
+        + server.normalized_jitter)
+        .clamp(0.0, 1.0);
+
+    if config.preferred_country == Some(server.country) {
+        base_score =
+            (base_score - config.preferred_country_bonus).clamp(0.0, 1.0);
+    }
+
+    if let Some(weight) = config.country_weights.get(&server.country) {
+        base_score = (base_score * weight).clamp(0.0, 1.0);
+    }
+
+    base_score
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scoring_config::{BandwidthModel, CapStrategy, NormalizeFn};
 
     type AnyResult = Result<(), Box<dyn std::error::Error>>;
 
+    // Computes the base score and the penalty of a server, as
+    // `compute_loads` does.
+    fn compute_score_breakdown(
+        server: ComputeScoreServerParams,
+        user_country: &Option<CountryCode>,
+        config: &ScoringConfig,
+    ) -> ScoreBreakdown {
+        let (penalty, _) = compute_penalty(
+            server.status_penalty,
+            server.status_cost,
+            server.partial_score,
+            user_country,
+            server.country,
+            server.status,
+            config,
+        );
+
+        ScoreBreakdown {
+            base_score: compute_base_score(&server, config),
+            penalty,
+        }
+    }
+
+    // Computes the score of a server, as `compute_loads` stores it in
+    // `Load::score`.
+    fn compute_score(
+        server: ComputeScoreServerParams,
+        user_country: &Option<CountryCode>,
+        config: &ScoringConfig,
+    ) -> f64 {
+        config.clamp_score(
+            compute_score_breakdown(server, user_country, config).score(),
+        )
+    }
+
     #[test_log::test]
     fn test_normalize() -> AnyResult {
         assert_eq!(normalize(SCORE_NORMALIZATION_FACTOR), 0.0);
@@ -240,6 +628,204 @@ mod tests {
         Ok(())
     }
 
+    #[test_log::test]
+    fn test_compute_travel_distance_legs() -> AnyResult {
+        let server_exit = Location {
+            latitude: 48.8566, // Paris
+            longitude: 2.3522,
+        };
+        let server_entry = Location {
+            latitude: 40.4168, // Madrid
+            longitude: -3.7038,
+        };
+        let client_location = Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        };
+
+        let (client_leg, internal_leg) = compute_travel_distance_legs(
+            &server_exit,
+            &server_entry,
+            &client_location,
+        );
+
+        assert_eq!(
+            internal_leg,
+            compute_distance_between(&server_entry, &server_exit)
+        );
+        assert_eq!(
+            client_leg + internal_leg,
+            compute_travel_distance(
+                &server_exit,
+                &server_entry,
+                &client_location
+            )
+        );
+
+        assert_eq!(
+            compute_user_travel_distance_legs(
+                &server_exit,
+                &server_entry,
+                &None,
+                DistanceMode::default()
+            ),
+            (0.0, 0.0)
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_compute_entry_scores() {
+        let exit = Location {
+            latitude: 48.8566, // Paris
+            longitude: 2.3522,
+        };
+        let geneva = Location {
+            latitude: 46.2044,
+            longitude: 6.1432,
+        };
+        let stockholm = Location {
+            latitude: 59.3293,
+            longitude: 18.0686,
+        };
+        let user = Location {
+            latitude: 47.3769, // Zurich
+            longitude: 8.5417,
+        };
+
+        let distance_to_score = |distance_in_km| {
+            compute_distance_score_from_distance(
+                distance_in_km,
+                None,
+                &ScoringConfig::default(),
+            )
+        };
+
+        let scores = compute_entry_scores(
+            &exit,
+            &[stockholm.clone(), geneva.clone()],
+            &user,
+            &ScoringConfig::default(),
+        );
+
+        let expected = |entry| {
+            distance_to_score(
+                compute_distance_between(&user, entry)
+                    + compute_distance_between(entry, &exit),
+            )
+        };
+        assert_eq!(scores, [expected(&stockholm), expected(&geneva)]);
+        assert!(scores[1] < scores[0]);
+        assert!(compute_entry_scores(
+            &exit,
+            &[],
+            &user,
+            &ScoringConfig::default()
+        )
+        .is_empty());
+    }
+
+    #[test_log::test]
+    fn test_distance_modes() {
+        let server_exit = Location {
+            latitude: 48.8566, // Paris
+            longitude: 2.3522,
+        };
+        let server_entry = Location {
+            latitude: 46.2044, // Geneva
+            longitude: 6.1432,
+        };
+        let client_location = Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        };
+
+        let legs = |mode| {
+            compute_travel_distance_legs_with_mode(
+                &server_exit,
+                &server_entry,
+                &client_location,
+                mode,
+            )
+        };
+
+        let to_exit = compute_distance_between(&client_location, &server_exit);
+        let to_entry =
+            compute_distance_between(&client_location, &server_entry);
+        let internal = compute_distance_between(&server_entry, &server_exit);
+
+        assert_eq!(legs(DistanceMode::Legacy), (to_exit, internal));
+        assert_eq!(legs(DistanceMode::EntryFirst), (to_entry, internal));
+        assert_eq!(legs(DistanceMode::EntryOnly), (to_entry, 0.0));
+
+        let expected_default = if legacy_enabled() {
+            DistanceMode::Legacy
+        } else {
+            DistanceMode::EntryFirst
+        };
+        assert_eq!(DistanceMode::default(), expected_default);
+    }
+
+    #[test_log::test]
+    fn test_distance_mode_nearest_endpoint() {
+        // A secure core server with an entry far from its exit
+        let server_entry = Location {
+            latitude: 64.1466, // Reykjavik
+            longitude: -21.9426,
+        };
+        let server_exit = Location {
+            latitude: -33.8688, // Sydney
+            longitude: 151.2093,
+        };
+        let internal = compute_distance_between(&server_entry, &server_exit);
+
+        let legs = |client_location: &Location, mode| {
+            compute_travel_distance_legs_with_mode(
+                &server_exit,
+                &server_entry,
+                client_location,
+                mode,
+            )
+        };
+
+        // Close to the exit, the nearest endpoint is the exit
+        let client_location = Location {
+            latitude: -37.8136, // Melbourne
+            longitude: 144.9631,
+        };
+        let to_exit = compute_distance_between(&client_location, &server_exit);
+        let to_entry =
+            compute_distance_between(&client_location, &server_entry);
+        assert!(to_exit < to_entry);
+        assert_eq!(
+            legs(&client_location, DistanceMode::NearestEndpoint),
+            (to_exit, internal)
+        );
+        assert_eq!(
+            legs(&client_location, DistanceMode::EntryFirst),
+            (to_entry, internal)
+        );
+
+        // Close to the entry, the nearest endpoint is the entry
+        let client_location = Location {
+            latitude: 51.5074, // London
+            longitude: -0.1278,
+        };
+        let to_exit = compute_distance_between(&client_location, &server_exit);
+        let to_entry =
+            compute_distance_between(&client_location, &server_entry);
+        assert!(to_entry < to_exit);
+        assert_eq!(
+            legs(&client_location, DistanceMode::NearestEndpoint),
+            (to_entry, internal)
+        );
+        assert_eq!(
+            legs(&client_location, DistanceMode::Legacy),
+            (to_exit, internal)
+        );
+    }
+
     #[test_log::test]
     fn test_compute_penalty() -> AnyResult {
         assert_eq!(
@@ -250,8 +836,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"FR")?,
-                STATUS_ENABLED | STATUS_VISIBLE
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             123.0, // Because the status penalty is high
@@ -261,8 +849,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"FR")?,
-                STATUS_ENABLED | STATUS_VISIBLE
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             1.0, // Because load score is high
@@ -272,8 +862,10 @@ mod tests {
                 0.99,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"FR")?,
-                STATUS_ENABLED | STATUS_VISIBLE
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             1.0, // Because countries are different
@@ -283,8 +875,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"GB")?,
-                STATUS_ENABLED | STATUS_VISIBLE
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             4.0, // Because countries are different and cost is 1
@@ -294,8 +888,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"GB")?,
-                STATUS_ENABLED | STATUS_VISIBLE
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             1000.0, // Because server active but not visible
@@ -305,8 +901,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"FR")?,
-                STATUS_ENABLED
+                STATUS_ENABLED,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             1000.0, // Because server is visible but not active
@@ -316,8 +914,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"FR")?,
-                STATUS_VISIBLE
+                STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
         assert_eq!(
             1000.0, // Because server is disabled and hidden
@@ -327,8 +927,10 @@ mod tests {
                 0.5,
                 &Some(CountryCode::try_from(b"FR")?),
                 CountryCode::try_from(b"FR")?,
-                0_u8
+                0_u8,
+                &ScoringConfig::default()
             )
+            .0
         );
 
         assert_eq!(
@@ -340,15 +942,149 @@ mod tests {
                 0.5,
                 &None,
                 CountryCode::try_from(b"FR")?,
-                STATUS_ENABLED | STATUS_VISIBLE
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig::default()
             )
+            .0
         );
 
         Ok(())
     }
 
     #[test_log::test]
-    fn test_compute_score() -> AnyResult {
+    fn test_compute_penalty_cost_tiers() {
+        let penalty = |cost: Cost, tiered_cost| {
+            compute_penalty(
+                0.0,
+                cost.to_byte(),
+                0.5,
+                &Some(CountryCode::try_from(b"FR").unwrap()),
+                CountryCode::try_from(b"GB").unwrap(),
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &ScoringConfig {
+                    tiered_cost,
+                    ..Default::default()
+                },
+            )
+        };
+        let cost = |tier, free_available| Cost {
+            tier,
+            free_available,
+        };
+
+        // The legacy cost bytes score as before, any non-zero byte is
+        // expensive.
+        assert_eq!(cost(1, false).to_byte(), 1_u8);
+        assert_eq!(cost(2, false).to_byte(), 2_u8);
+        assert_eq!(penalty(cost(0, false), false).0, 1.0);
+        assert_eq!(penalty(cost(1, false), false).0, 4.0);
+        assert_eq!(penalty(cost(2, false), false).0, 4.0);
+        assert_eq!(penalty(cost(0, true), false).0, 4.0);
+
+        assert_eq!(penalty(cost(0, false), true).0, 1.0);
+        assert_eq!(penalty(cost(1, false), true).0, 4.0);
+        assert!(!penalty(cost(0, true), true).1.expensive);
+        assert_eq!(penalty(cost(2, false), true).0, 7.0);
+        assert_eq!(penalty(cost(2, true), true).0, 7.0);
+    }
+
+    #[test_log::test]
+    fn test_explain_penalty() -> AnyResult {
+        let logical = Logical {
+            status_reference: crate::StatusReference {
+                cost: 1_u8,
+                ..Default::default()
+            },
+            exit_country: CountryCode::try_from(b"FR")?,
+            ..Default::default()
+        };
+        let status = ServerStatus {
+            status: STATUS_ENABLED,
+            partial_score: 0.995,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            explain_penalty(
+                &logical,
+                &status,
+                &Some(CountryCode::try_from(b"GB")?)
+            ),
+            PenaltyFlags {
+                disabled_or_hidden: true,
+                cross_country: true,
+                expensive: true,
+                saturated: true,
+            }
+        );
+        assert_eq!(
+            explain_penalty(
+                &logical,
+                &ServerStatus {
+                    status: STATUS_ENABLED | STATUS_VISIBLE,
+                    ..Default::default()
+                },
+                &Some(CountryCode::try_from(b"FR")?)
+            ),
+            PenaltyFlags::default()
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_compute_penalty_unknown_country_policy() -> AnyResult {
+        let assume_foreign = ScoringConfig {
+            unknown_country_policy: UnknownCountryPolicy::AssumeForeign,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            1.0, // Because the server is assumed to be in another country
+            compute_penalty(
+                0.0,
+                0_u8,
+                0.5,
+                &None,
+                CountryCode::try_from(b"FR")?,
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &assume_foreign
+            )
+            .0
+        );
+        assert_eq!(
+            4.0, // Because the server is assumed to be in another country
+            // and cost is 1
+            compute_penalty(
+                0.0,
+                1_u8,
+                0.5,
+                &None,
+                CountryCode::try_from(b"FR")?,
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &assume_foreign
+            )
+            .0
+        );
+        assert_eq!(
+            0.0, // The policy only applies when the user country is unknown
+            compute_penalty(
+                0.0,
+                0_u8,
+                0.5,
+                &Some(CountryCode::try_from(b"FR")?),
+                CountryCode::try_from(b"FR")?,
+                STATUS_ENABLED | STATUS_VISIBLE,
+                &assume_foreign
+            )
+            .0
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_estimated_bandwidth_mbps() {
         let paris = Location {
             latitude: 48.8566, // Paris
             longitude: 2.3522,
@@ -357,9 +1093,141 @@ mod tests {
             latitude: 43.6047, // Toulouse
             longitude: 1.4442,
         };
+        let config = ScoringConfig::default();
+
+        let distance = compute_travel_distance(&paris, &paris, &toulouse);
+        let bandwidth =
+            estimated_bandwidth_mbps(&paris, &paris, &toulouse, &config);
+        assert_eq!(bandwidth, BANDWITH_DISTANCE_FACTOR / distance);
+        assert_eq!(
+            normalize(bandwidth),
+            compute_distance_score(&paris, &paris, &Some(toulouse))
+        );
 
-        #[cfg(feature = "debug")]
-        let mut debug_fields = LoadDebugFields::default();
+        // Closer than 1 km, the estimate is capped
+        assert_eq!(
+            estimated_bandwidth_mbps(&paris, &paris, &paris, &config),
+            BANDWITH_DISTANCE_FACTOR
+        );
+    }
+
+    #[test_log::test]
+    fn test_cap_strategy() {
+        let server = |partial_score| ComputeScoreServerParams {
+            status_penalty: 0.0,
+            status_cost: 0_u8,
+            country: CountryCode::default(),
+            partial_score,
+            status: STATUS_ENABLED | STATUS_VISIBLE,
+            travel_distance_km: 500.0,
+            normalized_jitter: 0.0,
+            max_bandwidth_mbps: None,
+        };
+        let distance_score = compute_distance_score_from_distance(
+            500.0,
+            None,
+            &ScoringConfig::default(),
+        );
+        assert!(distance_score > 0.0 && distance_score < 1.0);
+        let (low_partial_score, high_partial_score) =
+            (distance_score / 2.0, (distance_score + 1.0) / 2.0);
+
+        let score = |partial_score, cap_strategy| {
+            compute_score(
+                server(partial_score),
+                &None,
+                &ScoringConfig {
+                    cap_strategy,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // The larger of the two scores wins
+        assert_eq!(score(low_partial_score, CapStrategy::Max), distance_score);
+        assert_eq!(
+            score(high_partial_score, CapStrategy::Max),
+            high_partial_score
+        );
+
+        let weighted_sum = CapStrategy::WeightedSum {
+            distance_weight: 0.25,
+            load_weight: 0.75,
+        };
+        assert_eq!(
+            score(high_partial_score, weighted_sum),
+            0.25 * distance_score + 0.75 * high_partial_score
+        );
+        // The load weighs in even when it is below the distance score
+        assert!(
+            score(0.0, weighted_sum) < score(low_partial_score, weighted_sum)
+        );
+
+        // The weighted sum is clamped
+        let overweighted = CapStrategy::WeightedSum {
+            distance_weight: 1.0,
+            load_weight: 2.0,
+        };
+        assert_eq!(score(high_partial_score, overweighted), 1.0);
+    }
+
+    #[test_log::test]
+    fn test_compute_score_raw() -> AnyResult {
+        let server =
+            |partial_score, normalized_jitter| ComputeScoreServerParams {
+                status_penalty: 0.0,
+                status_cost: 0_u8,
+                country: CountryCode::default(),
+                partial_score,
+                status: STATUS_ENABLED | STATUS_VISIBLE,
+                travel_distance_km: 0.0,
+                normalized_jitter,
+                max_bandwidth_mbps: None,
+            };
+        let config = ScoringConfig::default();
+
+        // The jitter pushes the base score above 1.0, which is clamped
+        let (base_score, jitter, penalty) =
+            compute_score_raw(server(0.998, 0.004), &None, &config);
+        assert_eq!((base_score, jitter), (0.998, 0.004));
+        assert!(base_score + jitter > 1.0);
+        assert_eq!(
+            compute_score(server(0.998, 0.004), &None, &config),
+            1.0 + penalty
+        );
+
+        // Within range, the raw components sum to the score
+        let (base_score, jitter, penalty) =
+            compute_score_raw(server(0.5, -0.004), &None, &config);
+        assert_eq!(
+            base_score + jitter + penalty,
+            compute_score(server(0.5, -0.004), &None, &config)
+        );
+
+        // The penalty is reported separately
+        let (_, _, penalty) = compute_score_raw(
+            ComputeScoreServerParams {
+                status: 0,
+                ..server(0.5, 0.0)
+            },
+            &None,
+            &config,
+        );
+        assert_eq!(penalty, UNAVAILABLE_PENALTY);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_compute_score() -> AnyResult {
+        let paris = Location {
+            latitude: 48.8566, // Paris
+            longitude: 2.3522,
+        };
+        let toulouse = Location {
+            latitude: 43.6047, // Toulouse
+            longitude: 1.4442,
+        };
 
         // In same country, far enough to apply distance cap.
         let score = compute_score(
@@ -369,14 +1237,16 @@ mod tests {
                 country: CountryCode::try_from(b"FR")?,
                 partial_score: 0.5,
                 status: STATUS_ENABLED | STATUS_VISIBLE,
-                exit_location: &paris,
-                entry_location: &paris,
+                travel_distance_km: compute_user_travel_distance(
+                    &paris,
+                    &paris,
+                    &Some(toulouse.clone()),
+                ),
                 normalized_jitter: 0_f64,
-                #[cfg(feature = "debug")]
-                debug: &mut debug_fields,
+                max_bandwidth_mbps: None,
             },
-            &Some(toulouse.clone()),
             &Some(CountryCode::try_from(b"FR")?),
+            &ScoringConfig::default(),
         );
 
         assert_eq!(
@@ -384,9 +1254,6 @@ mod tests {
             score
         );
 
-        #[cfg(feature = "debug")]
-        assert_eq!(debug_fields.partial_score, 0.5);
-
         // In different countries, close enough to avoid distance cap,
         // with penalty of 1.
         let score = compute_score(
@@ -396,28 +1263,370 @@ mod tests {
                 country: CountryCode::try_from(b"FR")?,
                 partial_score: 0.5,
                 status: STATUS_ENABLED | STATUS_VISIBLE,
-                exit_location: &Location {
-                    latitude: 45.8992, // Annecy
-                    longitude: 6.1294,
-                },
-                entry_location: &Location {
-                    latitude: 45.8992, // Annecy
-                    longitude: 6.1294,
-                },
+                travel_distance_km: compute_user_travel_distance(
+                    &Location {
+                        latitude: 45.8992, // Annecy
+                        longitude: 6.1294,
+                    },
+                    &Location {
+                        latitude: 45.8992, // Annecy
+                        longitude: 6.1294,
+                    },
+                    &Some(Location {
+                        latitude: 46.2044, // Geneva
+                        longitude: 6.1432,
+                    }),
+                ),
                 normalized_jitter: 0_f64,
-                #[cfg(feature = "debug")]
-                debug: &mut debug_fields,
+                max_bandwidth_mbps: None,
             },
-            &Some(Location {
-                latitude: 46.2044, // Geneva
-                longitude: 6.1432,
-            }),
             &Some(CountryCode::try_from(b"CH")?),
+            &ScoringConfig::default(),
         );
 
         assert_eq!(1.5, score);
-        #[cfg(feature = "debug")]
-        assert_eq!(debug_fields.partial_score, 0.5);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_max_bandwidth() {
+        // 738 Mbps estimated from the distance
+        let distance_km = 1000.0;
+        let config = ScoringConfig::default();
+        let uncapped =
+            compute_distance_score_from_distance(distance_km, None, &config);
+
+        assert_eq!(
+            compute_distance_score_from_distance(
+                distance_km,
+                Some(10_000.0),
+                &config
+            ),
+            uncapped
+        );
+        assert_eq!(
+            compute_distance_score_from_distance(
+                distance_km,
+                Some(500.0),
+                &config
+            ),
+            normalize(500.0)
+        );
+        assert!(
+            compute_distance_score_from_distance(
+                distance_km,
+                Some(500.0),
+                &config
+            ) > uncapped
+        );
+    }
+
+    #[test_log::test]
+    fn test_bandwidth_model() {
+        let score = |distance_km, model: &BandwidthModel| {
+            let config = ScoringConfig {
+                bandwidth_model: model.clone(),
+                ..Default::default()
+            };
+            compute_distance_score_from_distance(distance_km, None, &config)
+        };
+        let default = BandwidthModel::default();
+
+        // Short and long haul links with different factors
+        let piecewise = BandwidthModel::Piecewise(vec![
+            (500.0, 900_000.0),
+            (5000.0, 600_000.0),
+        ]);
+        assert_eq!(score(500.0, &piecewise), normalize(900_000.0 / 500.0));
+        assert_eq!(score(500.5, &piecewise), normalize(600_000.0 / 500.5));
+        assert_eq!(score(5000.0, &piecewise), normalize(600_000.0 / 5000.0));
+        // The last factor applies past the last breakpoint
+        assert_eq!(score(8000.0, &piecewise), normalize(600_000.0 / 8000.0));
+
+        // With the same factor on both sides, the breakpoint is continuous
+        let continuous = BandwidthModel::Piecewise(vec![
+            (500.0, BANDWITH_DISTANCE_FACTOR),
+            (f64::INFINITY, BANDWITH_DISTANCE_FACTOR),
+        ]);
+        for distance_km in [100.0, 499.9, 500.0, 500.1, 10_000.0] {
+            assert_eq!(
+                score(distance_km, &continuous),
+                score(distance_km, &default)
+            );
+        }
+
+        // Without breakpoints the default factor is used
+        assert_eq!(
+            score(1000.0, &BandwidthModel::Piecewise(Vec::new())),
+            score(1000.0, &default)
+        );
+    }
+
+    #[test_log::test]
+    fn test_normalize_fn() {
+        let score = |distance_km, config: &ScoringConfig| {
+            compute_distance_score_from_distance(distance_km, None, config)
+        };
+        let linear = ScoringConfig {
+            normalize_fn: Some(NormalizeFn::new(normalize)),
+            ..Default::default()
+        };
+        let log = ScoringConfig {
+            normalize_fn: Some(NormalizeFn::new(|bandwidth_mbps: f64| {
+                // The highest estimate, at 1 km or less, scores 0.0
+                1.0 - (1.0 + bandwidth_mbps).ln()
+                    / (1.0 + BANDWITH_DISTANCE_FACTOR).ln()
+            })),
+            ..Default::default()
+        };
+
+        for distance_km in [0.0, 1.0, 73.8, 500.0, 2000.0, 20_000.0] {
+            let default = score(distance_km, &ScoringConfig::default());
+            assert_eq!(
+                default.to_bits(),
+                normalize(
+                    BANDWITH_DISTANCE_FACTOR / f64::max(1.0, distance_km)
+                )
+                .to_bits()
+            );
+            assert_eq!(
+                default.to_bits(),
+                score(distance_km, &linear).to_bits()
+            );
+
+            let log_score = score(distance_km, &log);
+            assert!((0.0..=1.0).contains(&log_score), "{log_score}");
+        }
+
+        // Above 10 Gbps (under 73.8 km) the linear scores are negative, and
+        // clamped to 0.0 later on, while the logarithmic ones still differ.
+        assert!(score(10.0, &linear) < 0.0 && score(50.0, &linear) < 0.0);
+        assert!(
+            0.0 < score(10.0, &log) && score(10.0, &log) < score(50.0, &log)
+        );
+    }
+
+    #[test_log::test]
+    fn test_preferred_country_bonus() -> AnyResult {
+        let server = |status| ComputeScoreServerParams {
+            status_penalty: 0.0,
+            status_cost: 0_u8,
+            country: CountryCode::try_from(b"FR").unwrap(),
+            partial_score: 0.5,
+            status,
+            travel_distance_km: 0.0,
+            normalized_jitter: 0_f64,
+            max_bandwidth_mbps: None,
+        };
+        let user_country = Some(CountryCode::try_from(b"FR")?);
+        let config = ScoringConfig {
+            preferred_country: Some(CountryCode::try_from(b"FR")?),
+            preferred_country_bonus: 0.2,
+            ..Default::default()
+        };
+
+        let available = STATUS_ENABLED | STATUS_VISIBLE;
+        assert_eq!(
+            compute_score(server(available), &user_country, &config),
+            0.3
+        );
+
+        // The bonus is clamped so the score can't go below 0.0.
+        let config = ScoringConfig {
+            preferred_country_bonus: 2.0,
+            ..config
+        };
+        assert_eq!(
+            compute_score(server(available), &user_country, &config),
+            0.0
+        );
+
+        // The bonus doesn't move penalized servers out of their tier.
+        assert_eq!(
+            compute_score(server(STATUS_VISIBLE), &user_country, &config),
+            UNAVAILABLE_PENALTY
+        );
+
+        // Nor does a negative bonus, rejected by `ScoringConfig::validate`.
+        let negative = ScoringConfig {
+            preferred_country_bonus: -0.8,
+            ..config.clone()
+        };
+        assert_eq!(
+            compute_score(server(available), &user_country, &negative),
+            1.0
+        );
+
+        // No bonus for servers in another country.
+        let config = ScoringConfig {
+            preferred_country: Some(CountryCode::try_from(b"CH")?),
+            ..config
+        };
+        assert_eq!(
+            compute_score(server(available), &user_country, &config),
+            0.5
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_country_weights() -> AnyResult {
+        let server = |country: &[u8; 2], status| ComputeScoreServerParams {
+            status_penalty: 0.0,
+            status_cost: 0_u8,
+            country: CountryCode::try_from(country).unwrap(),
+            partial_score: 0.5,
+            status,
+            travel_distance_km: 0.0,
+            normalized_jitter: 0_f64,
+            max_bandwidth_mbps: None,
+        };
+        let config = ScoringConfig {
+            country_weights: [
+                (CountryCode::try_from(b"FR")?, 0.5),
+                (CountryCode::try_from(b"DE")?, 4.0),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let available = STATUS_ENABLED | STATUS_VISIBLE;
+        assert_eq!(
+            compute_score(server(b"FR", available), &None, &config),
+            0.25
+        );
+        assert_eq!(
+            compute_score(server(b"CH", available), &None, &config),
+            0.5
+        );
+
+        // The weighted score stays in its penalty tier.
+        assert_eq!(
+            compute_score(server(b"DE", available), &None, &config),
+            1.0
+        );
+        assert_eq!(
+            compute_score(server(b"FR", STATUS_VISIBLE), &None, &config),
+            UNAVAILABLE_PENALTY + 0.25
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_final_score_clamp() -> AnyResult {
+        let server = |status| ComputeScoreServerParams {
+            status_penalty: 0.0,
+            status_cost: 0_u8,
+            country: CountryCode::try_from(b"FR").unwrap(),
+            partial_score: 0.5,
+            status,
+            travel_distance_km: 0.0,
+            normalized_jitter: 0_f64,
+            max_bandwidth_mbps: None,
+        };
+        let user_country = Some(CountryCode::try_from(b"FR")?);
+        let config = ScoringConfig {
+            final_score_clamp: Some((0.0, 10.0)),
+            ..Default::default()
+        };
+
+        let available = STATUS_ENABLED | STATUS_VISIBLE;
+        assert_eq!(
+            compute_score(server(available), &user_country, &config),
+            0.5
+        );
+        assert_eq!(
+            compute_score(server(STATUS_VISIBLE), &user_country, &config),
+            10.0
+        );
+        assert_eq!(
+            compute_score(
+                server(STATUS_VISIBLE),
+                &user_country,
+                &ScoringConfig::default()
+            ),
+            UNAVAILABLE_PENALTY + 0.5
+        );
+        assert_eq!(config.score_bounds(), (0.0, 10.0));
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_ignore_partial_score() -> AnyResult {
+        let server = |partial_score, status| ComputeScoreServerParams {
+            status_penalty: 0.0,
+            status_cost: 0_u8,
+            country: CountryCode::try_from(b"FR").unwrap(),
+            partial_score,
+            status,
+            travel_distance_km: 100.0,
+            normalized_jitter: 0_f64,
+            max_bandwidth_mbps: None,
+        };
+        let user_country = Some(CountryCode::try_from(b"FR")?);
+        let config = ScoringConfig {
+            ignore_partial_score: true,
+            ..Default::default()
+        };
+
+        let available = STATUS_ENABLED | STATUS_VISIBLE;
+        let distance_score = compute_distance_score_from_distance(
+            100.0,
+            None,
+            &ScoringConfig::default(),
+        );
+        assert_eq!(
+            compute_score(server(0.9, available), &user_country, &config),
+            distance_score
+        );
+        assert_eq!(
+            compute_score(
+                server(0.9, available),
+                &user_country,
+                &ScoringConfig::default()
+            ),
+            0.9
+        );
+
+        // The partial score still counts for the saturation penalty.
+        assert_eq!(
+            compute_score(server(1.0, available), &user_country, &config),
+            distance_score + FOREIGN_OR_SATURATED_PENALTY
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_display_fraction() -> AnyResult {
+        let server = |status| ComputeScoreServerParams {
+            status_penalty: 0.0,
+            status_cost: 0_u8,
+            country: CountryCode::try_from(b"FR").expect("Invalid country"),
+            partial_score: 0.25,
+            status,
+            travel_distance_km: 0.0,
+            normalized_jitter: 0_f64,
+            max_bandwidth_mbps: None,
+        };
+
+        let breakdown = compute_score_breakdown(
+            server(STATUS_ENABLED | STATUS_VISIBLE),
+            &Some(CountryCode::try_from(b"FR")?),
+            &ScoringConfig::default(),
+        );
+        assert_eq!(breakdown.display_fraction(), 0.75);
+
+        let breakdown = compute_score_breakdown(
+            server(STATUS_VISIBLE),
+            &Some(CountryCode::try_from(b"FR")?),
+            &ScoringConfig::default(),
+        );
+        assert_eq!(breakdown.display_fraction(), 0.0);
 
         Ok(())
     }
@@ -1,6 +1,8 @@
 // -----------------------------------------------------------------------------
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
+use super::country_centroids::COUNTRY_CENTROIDS;
+use super::location::Location;
 
 // The UniFFI bindings require errors to implement std::error::Error trait.
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
@@ -14,19 +16,66 @@ pub enum CountryCodeConversionError {
 // Represents a country code in the format of two uppercase ASCII letters,
 // in the ISO 3166-1 alpha-2 format.
 //
-// The country code is stored as a 2-byte array.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+// The country code is stored as a 2-byte array. Every constructor uppercases
+// its input, so the derived `Eq` and `Hash` are case-insensitive with respect
+// to the input: a code built from "us" is the same map key as one built from
+// "US".
+//
+// Every constructor also checks that the bytes are ASCII. The only way around
+// it is the C bindings, where the logicals are read from memory as is, so the
+// accessors must not assume the bytes are valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "cffi", repr(C))]
 pub struct CountryCode([u8; 2]);
 
+// The default country code is "XX", a user-assigned code commonly used for
+// an unknown country. Unlike a zeroed code it is a valid country code, so
+// default values (e.g. `Logical::default()`) round-trip through validation
+// and serialization.
+impl Default for CountryCode {
+    fn default() -> Self {
+        Self(*b"XX")
+    }
+}
+
 impl CountryCode {
     pub fn as_bytes(&self) -> &[u8; 2] {
         &self.0
     }
 
+    /// Returns the country code as a string, or "XX" (the default, unknown
+    /// country) if its bytes are not ASCII, which can only happen when it
+    /// is passed through the C bindings.
     pub fn as_str(&self) -> &str {
-        // This should never panic because `Country` is always ASCII
-        str::from_utf8(&self.0).expect("invalid country code")
+        self.as_str_checked().unwrap_or("XX")
+    }
+
+    /// Returns the country code as a string, or an error if its bytes are
+    /// not ASCII.
+    pub fn as_str_checked(&self) -> Result<&str, CountryCodeConversionError> {
+        if !self.0.is_ascii() {
+            return Err(CountryCodeConversionError::InvalidFormat);
+        }
+
+        str::from_utf8(&self.0)
+            .map_err(|_| CountryCodeConversionError::InvalidFormat)
+    }
+
+    /// Returns the approximate geographic center of the country, or `None`
+    /// for the codes that are not assigned to a country (e.g. "XX").
+    ///
+    /// It is only meant as a coarse user location when the precise one is
+    /// unknown: the center of a large country can be far from its users.
+    pub fn centroid(&self) -> Option<Location> {
+        let index = COUNTRY_CENTROIDS
+            .binary_search_by_key(&self.0, |(code, _, _)| *code)
+            .ok()?;
+        let (_, latitude, longitude) = COUNTRY_CENTROIDS[index];
+
+        Some(Location {
+            latitude,
+            longitude,
+        })
     }
 }
 
@@ -73,7 +122,6 @@ impl AsRef<str> for CountryCode {
 
 impl<'a> From<&'a CountryCode> for &'a str {
     fn from(value: &'a CountryCode) -> Self {
-        // This should never panic because `Country` is always ASCII
         value.as_str()
     }
 }
@@ -126,6 +174,91 @@ impl<'de> serde::Deserialize<'de> for CountryCode {
 #[cfg(feature = "uniffi")]
 uniffi::custom_type!(CountryCode, String);
 
+// An optional country code with a C layout, zeroed when there is none.
+//
+// `Option<CountryCode>` has no C layout, so the C callers fill in the bytes
+// of the country code instead. Bytes that are not a valid country code are
+// read as no country.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct OptionalCountryCode([u8; 2]);
+
+impl OptionalCountryCode {
+    pub const fn from_bytes(bytes: [u8; 2]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 2] {
+        &self.0
+    }
+
+    /// Returns the country code, or `None` if the bytes are zeroed or not a
+    /// valid country code.
+    pub fn get(&self) -> Option<CountryCode> {
+        if self.0 == [0; 2] {
+            return None;
+        }
+
+        CountryCode::try_from(&self.0).ok()
+    }
+}
+
+impl From<Option<CountryCode>> for OptionalCountryCode {
+    fn from(value: Option<CountryCode>) -> Self {
+        Self(value.map_or([0; 2], |country| *country.as_bytes()))
+    }
+}
+
+impl From<OptionalCountryCode> for Option<CountryCode> {
+    fn from(value: OptionalCountryCode) -> Self {
+        value.get()
+    }
+}
+
+// Needed by `uniffi`, no country is an empty string
+impl TryFrom<String> for OptionalCountryCode {
+    type Error = CountryCodeConversionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Ok(Self::default());
+        }
+
+        Ok(Some(CountryCode::try_from(value)?).into())
+    }
+}
+
+// Needed by `uniffi`
+impl From<OptionalCountryCode> for String {
+    fn from(value: OptionalCountryCode) -> Self {
+        value.get().map(String::from).unwrap_or_default()
+    }
+}
+
+// Serialized as an optional country code
+#[cfg(feature = "serde")]
+impl serde::Serialize for OptionalCountryCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OptionalCountryCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Option::<CountryCode>::deserialize(deserializer)?.into())
+    }
+}
+
+#[cfg(feature = "uniffi")]
+uniffi::custom_type!(OptionalCountryCode, String);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +299,102 @@ mod tests {
         Ok(())
     }
 
+    #[test_log::test]
+    fn test_as_str_invalid_bytes() -> AnyResult {
+        assert_eq!(CountryCode::try_from("ch")?.as_str_checked()?, "CH");
+
+        // Only possible through the C bindings
+        let invalid = CountryCode(*b"\xc3\xa9");
+        assert_eq!(
+            invalid.as_str_checked(),
+            Err(CountryCodeConversionError::InvalidFormat)
+        );
+        assert_eq!(invalid.as_str(), "XX");
+        assert_eq!(invalid.to_string(), "XX");
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_lowercase_lookup() -> AnyResult {
+        let map = std::collections::HashMap::from([
+            (CountryCode::try_from("US")?, 1),
+            (CountryCode::try_from(b"ch")?, 2),
+        ]);
+
+        assert_eq!(map.get(&CountryCode::try_from("us")?), Some(&1));
+        assert_eq!(map.get(&CountryCode::try_from("uS")?), Some(&1));
+        assert_eq!(map.get(&CountryCode::try_from("CH")?), Some(&2));
+        assert_eq!(map.get(&CountryCode::try_from("fr")?), None);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_default_is_valid() -> AnyResult {
+        let default = CountryCode::default();
+        assert_eq!(CountryCode::try_from(default.as_bytes())?, default);
+        assert_eq!(CountryCode::try_from(default.as_str())?, default);
+        assert_eq!(default.as_str(), "XX");
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_centroid() -> AnyResult {
+        let switzerland = CountryCode::try_from("ch")?.centroid();
+        assert_eq!(
+            switzerland,
+            Some(Location {
+                latitude: 46.8,
+                longitude: 8.2,
+            })
+        );
+        assert_eq!(CountryCode::default().centroid(), None);
+
+        assert!(COUNTRY_CENTROIDS.is_sorted_by_key(|(code, _, _)| *code));
+        for (code, latitude, longitude) in COUNTRY_CENTROIDS {
+            let location = CountryCode::try_from(&code)?.centroid();
+            assert_eq!(
+                location,
+                Some(Location {
+                    latitude,
+                    longitude,
+                })
+            );
+            assert!(location.is_some_and(|location| location.is_scoreable()));
+        }
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_optional_country_code() -> AnyResult {
+        let none = OptionalCountryCode::default();
+        assert_eq!(none.as_bytes(), &[0; 2]);
+        assert_eq!(none.get(), None);
+        assert_eq!(OptionalCountryCode::from(None), none);
+
+        let iceland = OptionalCountryCode::from_bytes(*b"is");
+        assert_eq!(iceland.get(), Some(CountryCode::try_from("IS")?));
+        assert_eq!(
+            OptionalCountryCode::from(Some(CountryCode::try_from("IS")?)),
+            OptionalCountryCode::from_bytes(*b"IS")
+        );
+
+        // Bytes a C caller left invalid are not a country
+        assert_eq!(OptionalCountryCode::from_bytes(*b"\xc3\xa9").get(), None);
+
+        assert_eq!(String::from(none), "");
+        assert_eq!(OptionalCountryCode::try_from(String::new())?, none);
+        assert_eq!(
+            OptionalCountryCode::try_from("is".to_string())?.get(),
+            iceland.get()
+        );
+
+        Ok(())
+    }
+
     #[cfg(feature = "serde")]
     #[test_log::test]
     fn test_serialization() -> AnyResult {
@@ -0,0 +1,111 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use std::collections::HashMap;
+
+use super::compute_loads::first_parsing_error_logger;
+use super::compute_score::STATUS_ENABLED;
+use super::status::{Parser, ServerStatus};
+use super::{CountryCode, Logical, Result};
+
+/// Aggregated load of the servers of a country.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LoadStat {
+    // The number of servers in the country.
+    pub count: usize,
+    // The mean load of the servers, in percent.
+    pub mean_load: f64,
+    // The highest load of the servers, in percent.
+    pub max_load: u8,
+    // The number of enabled servers.
+    pub enabled_count: usize,
+}
+
+/// Computes the load statistics of the servers of each exit country.
+///
+/// The status of each logical is resolved as `compute_loads` does, so the
+/// servers missing from the status file, or whose status can't be parsed,
+/// count as disabled servers with a load of 0.
+pub fn country_load_stats(
+    logicals: &[Logical],
+    status_file: &[u8],
+) -> Result<HashMap<CountryCode, LoadStat>> {
+    let statuses = Parser::try_from(status_file)?;
+    let mut report_parsing_error = first_parsing_error_logger();
+
+    let status_is_unknown = ServerStatus::default();
+    let mut stats = HashMap::<CountryCode, LoadStat>::new();
+    for logical in logicals {
+        let status = statuses.get_for(
+            logical,
+            &status_is_unknown,
+            &mut report_parsing_error,
+        );
+
+        let stat = stats.entry(logical.exit_country).or_default();
+        stat.count += 1;
+        // Summed here, divided by the count below.
+        stat.mean_load += status.load as f64;
+        stat.max_load = stat.max_load.max(status.load);
+        stat.enabled_count += usize::from(status.status & STATUS_ENABLED != 0);
+    }
+
+    for stat in stats.values_mut() {
+        stat.mean_load /= stat.count as f64;
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{make_logical, make_status_file};
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_country_load_stats() -> AnyResult {
+        let logical = |index, country: &[u8; 2]| Logical {
+            exit_country: CountryCode::try_from(country)
+                .expect("Invalid country code"),
+            ..make_logical(index)
+        };
+        let logicals = [
+            logical(0, b"CH"),
+            logical(1, b"CH"),
+            logical(2, b"FR"),
+            logical(7, b"FR"), // Missing from the status file
+        ];
+
+        let status_file = make_status_file(&[
+            (3, 10, 0.0), // Enabled
+            (2, 30, 0.0), // Disabled
+            (3, 80, 0.0), // Enabled
+        ]);
+
+        let stats = country_load_stats(&logicals, &status_file)?;
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats[&CountryCode::try_from(b"CH")?],
+            LoadStat {
+                count: 2,
+                mean_load: 20.0,
+                max_load: 30,
+                enabled_count: 1,
+            }
+        );
+        assert_eq!(
+            stats[&CountryCode::try_from(b"FR")?],
+            LoadStat {
+                count: 2,
+                mean_load: 40.0,
+                max_load: 80,
+                enabled_count: 1,
+            }
+        );
+
+        Ok(())
+    }
+}
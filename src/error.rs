@@ -11,5 +11,63 @@ pub enum Error {
         "Length of Logicals ({servers}) and Loads ({loads}) are not the same."
     )]
     LengthsNotConsistent { servers: u64, loads: u64 },
+    #[error(
+        "Length of Logicals ({servers}) and distances ({distances}) are not the same."
+    )]
+    DistancesLengthNotConsistent { servers: u64, distances: u64 },
+    #[error(
+        "Travel distance {distance_km} of logical {index} is not a valid distance."
+    )]
+    InvalidDistance { index: u64, distance_km: f64 },
+    #[error(
+        "Length of Logicals ({servers}) and max bandwidths ({max_bandwidths}) are not the same."
+    )]
+    MaxBandwidthsLengthNotConsistent { servers: u64, max_bandwidths: u64 },
+    #[error(
+        "Max bandwidth {max_bandwidth_mbps} of logical {index} is not a valid bandwidth."
+    )]
+    InvalidMaxBandwidth { index: u64, max_bandwidth_mbps: f64 },
+    #[error(
+        "Length of Logicals ({servers}) and historical partial scores ({historical}) are not the same."
+    )]
+    HistoricalLengthNotConsistent { servers: u64, historical: u64 },
+    #[error(
+        "Length of Loads ({loads}) and partial scores ({partial_scores}) are not the same."
+    )]
+    PartialScoresLengthNotConsistent { loads: u64, partial_scores: u64 },
+    #[error(
+        "Number of servers of the status files ({first} and {second}) are not the same."
+    )]
+    RecordCountsNotConsistent { first: u64, second: u64 },
+    #[error(
+        "User location ({latitude}, {longitude}) is not a valid coordinate."
+    )]
+    InvalidUserLocation { latitude: f32, longitude: f32 },
+    #[error(
+        "Logicals {first} and {second} reference the same status index {index}."
+    )]
+    DuplicateStatusIndex { index: u32, first: u64, second: u64 },
+    #[error(
+        "Status file version {version} expects {expected_record_size} byte records, but {remainder} bytes are left over. The records may be laid out for another version."
+    )]
+    RecordSizeMismatch {
+        version: u32,
+        expected_record_size: u64,
+        remainder: u64,
+    },
+    #[error("Default server status is not valid: {0}")]
+    InvalidDefaultStatus(String),
+    #[error("failed to read status file with error {0}")]
+    IoError(String),
+    #[error("failed to convert JSON with error {0}")]
+    JsonError(String),
+    #[error("User country {0} is not a valid country code.")]
+    InvalidUserCountry(String),
+    #[error("Jitter range {low}..{high} is not a valid range.")]
+    InvalidJitterRange { low: f64, high: f64 },
+    #[error("Preferred country bonus {bonus} is not a valid bonus.")]
+    InvalidPreferredCountryBonus { bonus: f64 },
+    #[error("Weight {weight} of country {country} is not a valid weight.")]
+    InvalidCountryWeight { country: String, weight: f64 },
 }
 pub type Result<T> = std::result::Result<T, Error>;
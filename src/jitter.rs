@@ -1,16 +1,264 @@
 // -----------------------------------------------------------------------------
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
+use crate::compute_score::NORMALIZED_JITTER_RANGE;
+use crate::error::{Error, Result};
+
+/// The range the jitter is sampled from, in units of the 100 Mbps jitter
+/// band. Positive values are bandwidth gains, which improve the score.
+///
+/// The default range is `-0.5..0.5`, i.e. ±50 Mbps. An asymmetric range such
+/// as `-0.5..0.0` only ever worsens the scores, so that no server is
+/// transiently ranked above its actual score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JitterConfig {
+    #[cfg_attr(feature = "serde", serde(rename = "Low"))]
+    pub low: f64,
+    #[cfg_attr(feature = "serde", serde(rename = "High"))]
+    pub high: f64,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            low: -0.5,
+            high: 0.5,
+        }
+    }
+}
+
+impl JitterConfig {
+    /// Returns `Error::InvalidJitterRange` unless `low <= high`, both finite.
+    pub fn validate(&self) -> Result<()> {
+        if self.low.is_finite()
+            && self.high.is_finite()
+            && self.low <= self.high
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidJitterRange {
+                low: self.low,
+                high: self.high,
+            })
+        }
+    }
+
+    // Returns a normalized jitter value sampled from the range, which must
+    // be valid.
+    #[cfg(feature = "jitter")]
+    fn sample(&self, rng: &mut impl rand::Rng) -> f64 {
+        // An empty range can't be sampled from
+        let jitter = if self.low < self.high {
+            rng.random_range(self.low..self.high)
+        } else {
+            self.low
+        };
+
+        jitter * NORMALIZED_JITTER_RANGE
+    }
+}
+
+/// Returns whether the library was built with the `jitter` feature. Without
+/// it, scores are reproducible from the same inputs.
+pub const fn enabled() -> bool {
+    cfg!(feature = "jitter")
+}
+
+/// Returns whether two scores differ by less than the jitter band.
+///
+/// Two refreshes of the same server can differ by up to the width of the
+/// jitter band, so such scores should not cause the servers to be reordered.
+/// The band is the same whether the `jitter` feature is enabled or not.
+pub fn score_within_jitter(a: f64, b: f64) -> bool {
+    (a - b).abs() < NORMALIZED_JITTER_RANGE.abs()
+}
+
+// Returns a generator seeded from the OS entropy source, or `None` if the
+// entropy source is not available (e.g. on some locked-down embedded
+// targets), in which case `rand::rng()` would panic.
 #[cfg(feature = "jitter")]
+fn os_seeded_rng() -> Option<rand::rngs::StdRng> {
+    use rand::SeedableRng as _;
+
+    match rand::rngs::StdRng::try_from_os_rng() {
+        Ok(rng) => Some(rng),
+        Err(error) => {
+            log::warn!(
+                "Failed to seed the jitter generator: {error}. \
+                Scores are computed without jitter."
+            );
+            None
+        }
+    }
+}
+
+/// Returns a generator of normalized jitter values to add to the scores.
+///
+/// When the `jitter` feature is disabled, or when the OS entropy source is
+/// not available, the generator always returns 0.0.
 pub fn generator() -> impl FnMut() -> f64 {
-    use crate::compute_score::NORMALIZED_JITTER_RANGE;
-    use rand::Rng as _;
+    unchecked_generator(JitterConfig::default())
+}
+
+/// Same as `generator`, but samples the jitter from the given range instead
+/// of the default one.
+///
+/// Returns an error if the range is invalid.
+pub fn generator_with(config: &JitterConfig) -> Result<impl FnMut() -> f64> {
+    config.validate()?;
 
-    let mut rng = rand::rng();
-    move || rng.random_range(-0.5..0.5) * NORMALIZED_JITTER_RANGE
+    Ok(unchecked_generator(*config))
+}
+
+// The range must be valid.
+#[cfg(feature = "jitter")]
+fn unchecked_generator(config: JitterConfig) -> impl FnMut() -> f64 {
+    let mut rng = os_seeded_rng();
+    move || rng.as_mut().map_or(0_f64, |rng| config.sample(rng))
 }
 
 #[cfg(not(feature = "jitter"))]
-pub fn generator() -> impl FnMut() -> f64 {
+fn unchecked_generator(_config: JitterConfig) -> impl FnMut() -> f64 {
     move || 0_f64
 }
+
+// Same as `generator_with`, but the jitter sequence is fully determined by
+// the given seed.
+#[cfg(all(feature = "jitter", feature = "serde"))]
+pub fn seeded_generator(
+    seed: u64,
+    config: &JitterConfig,
+) -> Result<impl FnMut() -> f64> {
+    use rand::SeedableRng as _;
+
+    config.validate()?;
+
+    let config = *config;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    Ok(move || config.sample(&mut rng))
+}
+
+#[cfg(all(not(feature = "jitter"), feature = "serde"))]
+pub fn seeded_generator(
+    _seed: u64,
+    config: &JitterConfig,
+) -> Result<impl FnMut() -> f64> {
+    config.validate()?;
+
+    Ok(move || 0_f64)
+}
+
+// Returns a random key for `keyed`, or `None` when the scores are computed
+// without jitter.
+#[cfg(feature = "jitter")]
+pub(crate) fn random_key() -> Option<u64> {
+    use rand::Rng as _;
+
+    os_seeded_rng().map(|mut rng| rng.random())
+}
+
+#[cfg(not(feature = "jitter"))]
+pub(crate) fn random_key() -> Option<u64> {
+    None
+}
+
+// Returns the normalized jitter of the server at the given index, sampled
+// from the given range, which must be valid. The same key and index always
+// give the same jitter, so that a server scored several times keeps the same
+// score.
+#[cfg(feature = "jitter")]
+pub(crate) fn keyed(
+    key: Option<u64>,
+    index: usize,
+    config: &JitterConfig,
+) -> f64 {
+    use rand::SeedableRng as _;
+
+    let Some(key) = key else {
+        return 0_f64;
+    };
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(key ^ index as u64);
+    config.sample(&mut rng)
+}
+
+#[cfg(not(feature = "jitter"))]
+pub(crate) fn keyed(
+    _key: Option<u64>,
+    _index: usize,
+    _config: &JitterConfig,
+) -> f64 {
+    0_f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_score_within_jitter() {
+        assert!(score_within_jitter(0.5, 0.5));
+        assert!(score_within_jitter(0.5, 0.509));
+        assert!(score_within_jitter(0.509, 0.5));
+        assert!(!score_within_jitter(0.5, 0.511));
+        assert!(!score_within_jitter(1.5, 0.5));
+
+        let mut generator = generator();
+        for _ in 0..1000 {
+            assert!(score_within_jitter(0.5 + generator(), 0.5 + generator()));
+        }
+    }
+
+    #[test_log::test]
+    fn test_generator_with() -> std::result::Result<(), Error> {
+        // Only ever worsens the scores, by up to 50 Mbps
+        let mut generator = generator_with(&JitterConfig {
+            low: -0.5,
+            high: 0.0,
+        })?;
+        for _ in 0..1000 {
+            let jitter = generator();
+            assert!((0.0..=-0.5 * NORMALIZED_JITTER_RANGE).contains(&jitter));
+        }
+
+        // An empty range always gives the same jitter
+        let mut generator = generator_with(&JitterConfig {
+            low: 0.25,
+            high: 0.25,
+        })?;
+        let expected = if enabled() {
+            0.25 * NORMALIZED_JITTER_RANGE
+        } else {
+            0.0
+        };
+        assert_eq!(generator(), expected);
+
+        for (low, high) in [(0.5, -0.5), (f64::NAN, 0.5), (0.0, f64::INFINITY)]
+        {
+            assert!(matches!(
+                generator_with(&JitterConfig { low, high }),
+                Err(Error::InvalidJitterRange { .. })
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_log::test]
+    fn test_seeded_generator_with() -> std::result::Result<(), Error> {
+        let config = JitterConfig {
+            low: -0.5,
+            high: 0.0,
+        };
+
+        let mut a = seeded_generator(42, &config)?;
+        let mut b = seeded_generator(42, &config)?;
+        for _ in 0..100 {
+            assert_eq!(a(), b());
+        }
+
+        Ok(())
+    }
+}
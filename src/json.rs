@@ -0,0 +1,107 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use super::compute_loads::compute_loads;
+use super::error::*;
+use super::{CountryCode, Load, Location, Logical};
+
+/// Computes the loads of the logicals given as JSON, and returns them as a
+/// JSON array of `Load`.
+///
+/// `logicals_json` is a JSON array of `Logical` and `user_location_json` a
+/// JSON `Location`, in the same format as their serde representation. This
+/// lets bindings pass the /logicals response through without converting it
+/// to `Logical` on their side.
+pub fn compute_loads_from_json(
+    logicals_json: &[u8],
+    status_file: &[u8],
+    user_location_json: Option<&[u8]>,
+    user_country: Option<&str>,
+) -> Result<String> {
+    let logicals = serde_json::from_slice::<Vec<Logical>>(logicals_json)
+        .map_err(json_error)?;
+
+    let user_location = user_location_json
+        .map(serde_json::from_slice::<Location>)
+        .transpose()
+        .map_err(json_error)?;
+
+    compute_loads_json(&logicals, status_file, &user_location, user_country)
+}
+
+// Does the work of `compute_loads_from_json` once the logicals and the user
+// location are deserialized.
+pub(crate) fn compute_loads_json(
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_location: &Option<Location>,
+    user_country: Option<&str>,
+) -> Result<String> {
+    let user_country = user_country
+        .map(|country| {
+            CountryCode::try_from(country)
+                .map_err(|_| Error::InvalidUserCountry(country.to_string()))
+        })
+        .transpose()?;
+
+    let mut loads = vec![Load::default(); logicals.len()];
+    compute_loads(
+        &mut loads,
+        logicals,
+        status_file,
+        user_location,
+        &user_country,
+    )?;
+
+    serde_json::to_string(&loads).map_err(json_error)
+}
+
+fn json_error(error: serde_json::Error) -> Error {
+    Error::JsonError(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_compute_loads_from_json() -> AnyResult {
+        let logicals = serde_json::to_vec(&[Logical::default()])?;
+        let user_location = serde_json::to_vec(&Location {
+            latitude: 46.2044,
+            longitude: 6.1432,
+        })?;
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 42_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 0
+        ];
+
+        let loads = compute_loads_from_json(
+            &logicals,
+            &status_file,
+            Some(&user_location),
+            Some("ch"),
+        )?;
+        let loads = serde_json::from_str::<Vec<Load>>(&loads)?;
+        assert_eq!(loads.len(), 1);
+        assert_eq!(loads[0].load, 42);
+        assert!(loads[0].distance_km > 0.0);
+
+        assert!(matches!(
+            compute_loads_from_json(b"{}", &status_file, None, None),
+            Err(Error::JsonError(_))
+        ));
+        assert!(matches!(
+            compute_loads_from_json(&logicals, &status_file, Some(b"[]"), None),
+            Err(Error::JsonError(_))
+        ));
+        assert!(matches!(
+            compute_loads_from_json(&logicals, &status_file, None, Some("CHE")),
+            Err(Error::InvalidUserCountry(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -9,27 +9,90 @@
 //! - A `compute_loads` function that computes the load for each server based on
 //!   the user location and status file.
 //! - A parser for the binary status file.
+//! - A `validate_references` function that checks the logicals against the
+//!   status file before scoring.
 
 #[cfg(feature = "uniffi")]
 mod bindings_uniffi;
+#[cfg(feature = "serde")]
+mod combined_blob;
 mod compute_loads;
 mod compute_score;
 mod coord;
+mod country_centroids;
 mod country_code;
+mod country_stats;
 mod error;
 mod jitter;
+#[cfg(feature = "serde")]
+mod json;
 mod load;
 mod location;
 mod logical;
+mod score;
+mod scorer;
+mod scoring_config;
+#[cfg(feature = "serde")]
+mod snapshot;
 mod status;
+#[cfg(test)]
+mod test_fixtures;
+mod validate;
 
-pub use compute_loads::compute_loads;
-pub use country_code::{CountryCode, CountryCodeConversionError};
+#[cfg(feature = "serde")]
+pub use combined_blob::CombinedBlob;
+pub use compute_loads::{
+    compute_loads, compute_loads_batch, compute_loads_blended,
+    compute_loads_checked, compute_loads_columnar, compute_loads_dual,
+    compute_loads_filtered, compute_loads_merge, compute_loads_smoothed,
+    compute_loads_with_config, compute_loads_with_country_caps,
+    compute_loads_with_country_override, compute_loads_with_default,
+    compute_loads_with_distances, compute_loads_with_jitter,
+    compute_loads_with_max_bandwidths, compute_loads_with_stats,
+    project_server, score_at_load,
+};
+pub use compute_score::{
+    compute_entry_scores, compute_score_raw, compute_travel_distance,
+    compute_travel_distance_legacy, compute_travel_distance_legs,
+    compute_travel_distance_modern, estimated_bandwidth_mbps, explain_penalty,
+    legacy_enabled, ComputeScoreServerParams, PenaltyFlags,
+    STATUS_AUTOCONNECTABLE, STATUS_ENABLED, STATUS_VISIBLE,
+};
+pub use country_code::{
+    CountryCode, CountryCodeConversionError, OptionalCountryCode,
+};
+pub use country_stats::{country_load_stats, LoadStat};
 pub use error::{Error, Result};
-pub use load::Load;
+pub use jitter::{
+    enabled as jitter_enabled, generator as jitter_generator,
+    generator_with as jitter_generator_with, score_within_jitter, JitterConfig,
+};
+#[cfg(feature = "serde")]
+pub use json::compute_loads_from_json;
+#[cfg(feature = "serde")]
+pub use load::write_csv;
+pub use load::{
+    iter_by_score, loads_digest, rank_diff, ranks, score_gap_to_best,
+    score_percentiles, Load, Quality, RankChange,
+};
 pub use location::Location;
 pub use logical::*;
-pub use status::Parser;
+pub use score::Score;
+pub use scorer::Scorer;
+pub use scoring_config::{
+    BandwidthModel, CapStrategy, DistanceMode, NormalizeFn, QualityThresholds,
+    ScoringConfig, StatusPenaltyPrecedence, UnknownCountryPolicy,
+};
+#[cfg(feature = "serde")]
+pub use snapshot::ScoringSnapshot;
+#[cfg(feature = "mmap")]
+pub use status::MmapParser;
+pub use status::{
+    lint_status_file, merge_status_prefer_enabled, status_file_from_loads,
+    DefaultedServer, ParseErrorCollector, Parser, Partitioned, ServerStatus,
+    StatusCounts, StatusFileReport, Version, MAX_LOAD, PARTIAL_SCORE_RANGE,
+};
+pub use validate::validate_references;
 
 #[cfg(feature = "uniffi")]
 uniffi::setup_scaffolding!();
@@ -37,5 +100,12 @@ uniffi::setup_scaffolding!();
 #[cfg(feature = "cffi")]
 pub mod bindings_cffi;
 
-#[cfg(any(feature = "test_utils_backend", feature = "test_utils_webview"))]
+#[cfg(feature = "wasm")]
+pub mod bindings_wasm;
+
+#[cfg(any(
+    feature = "test_utils_fixtures",
+    feature = "test_utils_synthetic",
+    feature = "test_utils_webview"
+))]
 pub mod test_utils;
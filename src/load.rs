@@ -1,6 +1,7 @@
 // -----------------------------------------------------------------------------
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
+use crate::score::Score;
 
 /// Contains additional debug fields when this lib is built with the "debug"
 /// feature enabled.
@@ -13,6 +14,12 @@
 pub struct LoadDebugFields {
     #[cfg_attr(feature = "serde", serde(rename = "PartialScore"))]
     pub partial_score: f64,
+    // The distance from the client to the server.
+    #[cfg_attr(feature = "serde", serde(rename = "ClientLegKm"))]
+    pub client_leg_km: f64,
+    // The distance from the server entry to the server exit.
+    #[cfg_attr(feature = "serde", serde(rename = "InternalLegKm"))]
+    pub internal_leg_km: f64,
 }
 
 /// Contains an up to date status, load and score for a server.
@@ -30,9 +37,747 @@ pub struct Load {
     pub is_autoconnectable: bool,
     #[cfg_attr(feature = "serde", serde(rename = "Load"))]
     pub load: u8,
+    // The raw status byte from the status file, the flags above are decoded
+    // from it. Bits unknown to this version of the library are preserved.
+    #[cfg_attr(feature = "serde", serde(rename = "StatusByte"))]
+    pub status: u8,
     #[cfg_attr(feature = "serde", serde(rename = "Score"))]
-    pub score: f64,
+    pub score: Score,
+    // The travel distance used to compute the score, 0.0 when the user
+    // location is unknown.
+    #[cfg_attr(feature = "serde", serde(rename = "DistanceKm"))]
+    pub distance_km: f64,
+    // The quality of the server as a fraction in the range 0.0..=1.0,
+    // suitable for a progress bar. Penalized servers are 0.0 and the best
+    // servers are close to 1.0. Only computed when enabled in the
+    // `ScoringConfig`, otherwise 0.0.
+    #[cfg_attr(feature = "serde", serde(rename = "DisplayFraction"))]
+    pub display_fraction: f64,
+    // The penalty component of `score`, as computed by the scoring, 0.0 when
+    // the server is not penalized.
+    #[cfg_attr(feature = "serde", serde(rename = "Penalty", default))]
+    pub penalty: f64,
     #[cfg(feature = "debug")]
     #[cfg_attr(feature = "serde", serde(rename = "Debug"))]
     pub debug: LoadDebugFields,
 }
+
+/// A coarse category of the connection quality of a server, for display.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum Quality {
+    // The server is disabled, hidden or otherwise unavailable.
+    Unavailable,
+    Low,
+    Medium,
+    High,
+}
+
+impl Load {
+    /// Returns whether a penalty was added to the base score.
+    pub fn is_penalized(&self) -> bool {
+        self.penalty > 0.0
+    }
+
+    /// Returns the base component of the score, i.e. the score without its
+    /// penalty.
+    pub fn base_score(&self) -> f64 {
+        self.score.value() - self.penalty
+    }
+
+    /// Returns the quality category of the server, using the default
+    /// `ScoringConfig`.
+    pub fn quality(&self) -> Quality {
+        self.quality_with_config(&crate::ScoringConfig::default())
+    }
+
+    /// Returns the quality category of the server, using the thresholds of
+    /// the given `ScoringConfig`.
+    pub fn quality_with_config(
+        &self,
+        config: &crate::ScoringConfig,
+    ) -> Quality {
+        let thresholds = &config.quality_thresholds;
+
+        if !self.is_enabled
+            || !self.is_visible
+            || self.score.value() >= crate::compute_score::UNAVAILABLE_PENALTY
+        {
+            Quality::Unavailable
+        } else if self.score.value() < thresholds.high {
+            Quality::High
+        } else if self.score.value() < thresholds.medium {
+            Quality::Medium
+        } else {
+            Quality::Low
+        }
+    }
+
+    /// Returns the index of the bucket the load falls into.
+    ///
+    /// `buckets` are ascending load thresholds (in percent, like
+    /// `Load::load`). The bucket index is the number of thresholds the load
+    /// reaches, so loads below the first threshold are in bucket 0 and loads
+    /// at or above the last one are in bucket `buckets.len()`.
+    pub fn load_bucket(&self, buckets: &[u8]) -> usize {
+        buckets.partition_point(|&threshold| threshold <= self.load)
+    }
+
+    /// Returns the score as a fixed-point number with 9 decimals, which,
+    /// unlike the `f64` score, hashes the same on every platform.
+    ///
+    /// Scores out of the `i64` range saturate, and NaN is 0.
+    pub fn score_fixed(&self) -> i64 {
+        (self.score.value() * 1e9).round() as i64
+    }
+
+    /// Size of the encoding returned by `Load::to_wire_le`.
+    pub const WIRE_SIZE: usize = 37;
+
+    /// Encodes the load in a platform independent layout, unlike the
+    /// `repr(C)` layout used by the C bindings.
+    ///
+    /// The flags (`is_enabled`, `is_visible`, `is_autoconnectable`) are
+    /// encoded as 0 or 1 and followed by the load and the raw status byte,
+    /// one byte each. Then come `score`, `distance_km`, `display_fraction`
+    /// and `penalty` as little endian 64-bit floats. The debug fields are not
+    /// encoded.
+    pub fn to_wire_le(&self) -> [u8; Self::WIRE_SIZE] {
+        let mut wire = [0_u8; Self::WIRE_SIZE];
+        wire[0] = self.is_enabled as u8;
+        wire[1] = self.is_visible as u8;
+        wire[2] = self.is_autoconnectable as u8;
+        wire[3] = self.load;
+        wire[4] = self.status;
+        wire[5..13].copy_from_slice(&self.score.value().to_le_bytes());
+        wire[13..21].copy_from_slice(&self.distance_km.to_le_bytes());
+        wire[21..29].copy_from_slice(&self.display_fraction.to_le_bytes());
+        wire[29..37].copy_from_slice(&self.penalty.to_le_bytes());
+        wire
+    }
+}
+
+/// Describes how the rank of a server changed between two scorings.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankChange {
+    // The position of the server in the loads (and logicals) slices.
+    pub index: usize,
+    // The 0-based rank of the server in the old loads, 0 is the best.
+    pub old_rank: usize,
+    // The 0-based rank of the server in the new loads, 0 is the best.
+    pub new_rank: usize,
+    // The new score minus the old score.
+    pub score_delta: f64,
+}
+
+// Returns the indices of the loads in ascending score order, ties are broken
+// by index.
+fn score_order(loads: &[Load]) -> Vec<usize> {
+    let mut order = (0..loads.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| loads[index].score);
+    order
+}
+
+/// Returns the 0-based rank of each load, 0 being the best (lowest) score.
+///
+/// `ranks[i]` is the rank of `loads[i]`, i.e. this is the inverse of the
+/// permutation sorting the loads by score. Scores are compared with
+/// `f64::total_cmp` and ties are broken by index.
+pub fn ranks(loads: &[Load]) -> Vec<u32> {
+    let mut ranks = vec![0; loads.len()];
+    for (rank, index) in score_order(loads).into_iter().enumerate() {
+        ranks[index] = rank as u32;
+    }
+    ranks
+}
+
+/// Iterates over the logicals and their loads in ascending score order, i.e.
+/// best server first.
+///
+/// Only a permutation of the indices is allocated, the logicals and loads
+/// are not cloned. Servers past the end of the shorter slice are ignored.
+pub fn iter_by_score<'a>(
+    logicals: &'a [crate::Logical],
+    loads: &'a [Load],
+) -> impl Iterator<Item = (&'a crate::Logical, &'a Load)> {
+    let len = usize::min(logicals.len(), loads.len());
+
+    score_order(&loads[..len])
+        .into_iter()
+        .map(move |index| (&logicals[index], &loads[index]))
+}
+
+/// Returns the servers whose rank changed between two scorings.
+///
+/// Both slices must be computed from the same logicals in the same order,
+/// as servers are matched by their position. Servers past the end of the
+/// shorter slice are ignored.
+pub fn rank_diff(old: &[Load], new: &[Load]) -> Vec<RankChange> {
+    let old_ranks = ranks(old);
+    let new_ranks = ranks(new);
+
+    std::iter::zip(old_ranks, new_ranks)
+        .enumerate()
+        .filter(|(_, (old_rank, new_rank))| old_rank != new_rank)
+        .map(|(index, (old_rank, new_rank))| RankChange {
+            index,
+            old_rank: old_rank as usize,
+            new_rank: new_rank as usize,
+            score_delta: new[index].score.value() - old[index].score.value(),
+        })
+        .collect()
+}
+
+/// Returns by how much the score of `loads[index]` must decrease for it to
+/// become the best (lowest) score, or how far ahead of the next best server
+/// it is, as a negative value, when it is already the best.
+///
+/// This is the plain difference of the scores, penalties included: a
+/// penalized server can't close the gap to an unpenalized one by improving
+/// its base score alone. It is 0.0 when the server is tied with the best
+/// server, or is the only one.
+///
+/// Panics if `index` is out of bounds.
+pub fn score_gap_to_best(loads: &[Load], index: usize) -> f64 {
+    let score = loads[index].score;
+    let best_other = loads
+        .iter()
+        .enumerate()
+        .filter(|&(other, _)| other != index)
+        .map(|(_, load)| load.score)
+        .min();
+
+    let Some(best_other) = best_other else {
+        return 0.0;
+    };
+
+    score.value() - best_other.value()
+}
+
+/// Returns a platform independent fingerprint of the scoring result, to
+/// detect changes between two scorings.
+///
+/// It hashes the `Load::score_fixed` score and the flags of each load, in
+/// order, with the 64-bit FNV-1a hash. The other fields are not hashed.
+pub fn loads_digest(loads: &[Load]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut digest = FNV_OFFSET_BASIS;
+    let mut hash = |bytes: &[u8]| {
+        for byte in bytes {
+            digest = (digest ^ u64::from(*byte)).wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    for load in loads {
+        hash(&load.score_fixed().to_le_bytes());
+        hash(&[
+            load.is_enabled as u8,
+            load.is_visible as u8,
+            load.is_autoconnectable as u8,
+        ]);
+    }
+
+    digest
+}
+
+/// Returns estimates of the given percentiles of the scores, without sorting
+/// the loads.
+///
+/// `ps` are fractions in the range 0.0..=1.0, e.g. 0.5 for the median, out
+/// of range values are clamped. Scores of 1.0 and above, i.e. penalized
+/// servers, are not part of the distribution. Each percentile is estimated
+/// in a single pass with the P² algorithm, in constant memory, and is exact
+/// when there are 5 scores or fewer. The percentiles are NaN when there are
+/// no scores to estimate them from.
+pub fn score_percentiles(loads: &[Load], ps: &[f64]) -> Vec<f64> {
+    let mut estimators = ps
+        .iter()
+        .map(|&p| P2Estimator::new(p.clamp(0.0, 1.0)))
+        .collect::<Vec<_>>();
+
+    for load in loads {
+        let score = load.score.value();
+        if score.is_nan() || score >= 1.0 {
+            continue;
+        }
+
+        for estimator in &mut estimators {
+            estimator.add(score);
+        }
+    }
+
+    estimators.iter().map(P2Estimator::estimate).collect()
+}
+
+// Estimates a percentile with the P² algorithm (Jain and Chlamtac, 1985),
+// which tracks 5 markers: the minimum, the maximum, the percentile and the
+// percentiles halfway between them, adjusting their heights with a
+// piecewise-parabolic interpolation as the observations come in.
+struct P2Estimator {
+    p: f64,
+    count: usize,
+    // The heights of the markers, i.e. the first observations, sorted, until
+    // there are 5 of them.
+    heights: [f64; 5],
+    // The actual positions of the markers.
+    positions: [f64; 5],
+    // The desired positions of the markers.
+    desired: [f64; 5],
+    // The increments of the desired positions for each observation.
+    increments: [f64; 5],
+}
+
+impl P2Estimator {
+    fn new(p: f64) -> Self {
+        Self {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [0.0, 1.0, 2.0, 3.0, 4.0],
+            desired: [0.0, 2.0 * p, 4.0 * p, 2.0 + 2.0 * p, 4.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn add(&mut self, x: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            self.heights[..self.count].sort_by(f64::total_cmp);
+            return;
+        }
+        self.count += 1;
+
+        let q = &mut self.heights;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            // q[0] <= x < q[4], so k is in 0..=3.
+            q.partition_point(|&height| height <= x) - 1
+        };
+
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in
+            std::iter::zip(&mut self.desired, self.increments)
+        {
+            *desired += increment;
+        }
+
+        let n = &mut self.positions;
+        for i in 1..=3 {
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0)
+                || (d <= -1.0 && n[i - 1] - n[i] < -1.0)
+            {
+                let d = d.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i])
+                            / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1])
+                                / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    fn estimate(&self) -> f64 {
+        match self.count {
+            0 => f64::NAN,
+            1..=5 => {
+                // Linear interpolation between the closest ranks.
+                let sorted = &self.heights[..self.count];
+                let rank = self.p * (self.count - 1) as f64;
+                let below = rank.floor() as usize;
+                let above = rank.ceil() as usize;
+                sorted[below]
+                    + (sorted[above] - sorted[below]) * (rank - below as f64)
+            }
+            // The extreme markers are the exact minimum and maximum.
+            _ if self.p == 0.0 => self.heights[0],
+            _ if self.p == 1.0 => self.heights[4],
+            _ => self.heights[2],
+        }
+    }
+}
+
+/// Writes the loads and their logicals as CSV, with a header row.
+///
+/// The column names match the serde field names. When the `debug` feature
+/// is enabled the debug fields are written as additional columns.
+#[cfg(feature = "serde")]
+pub fn write_csv(
+    loads: &[Load],
+    logicals: &[crate::Logical],
+    mut w: impl std::io::Write,
+) -> std::io::Result<()> {
+    if loads.len() != logicals.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Length of Logicals ({}) and Loads ({}) are not the same.",
+                logicals.len(),
+                loads.len()
+            ),
+        ));
+    }
+
+    write!(
+        w,
+        "Index,ExitCountry,Latitude,Longitude,IsEnabled,IsVisible,Load,Score"
+    )?;
+    #[cfg(feature = "debug")]
+    write!(w, ",PartialScore,ClientLegKm,InternalLegKm")?;
+    writeln!(w)?;
+
+    for (index, (load, logical)) in loads.iter().zip(logicals).enumerate() {
+        write!(
+            w,
+            "{},{},{},{},{},{},{},{}",
+            index,
+            logical.exit_country,
+            logical.exit_location.latitude,
+            logical.exit_location.longitude,
+            load.is_enabled,
+            load.is_visible,
+            load.load,
+            load.score
+        )?;
+        #[cfg(feature = "debug")]
+        write!(
+            w,
+            ",{},{},{}",
+            load.debug.partial_score,
+            load.debug.client_leg_km,
+            load.debug.internal_leg_km
+        )?;
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_loads(scores: &[f64]) -> Vec<Load> {
+        scores
+            .iter()
+            .map(|&score| Load {
+                score: Score(score),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test_log::test]
+    fn test_score_percentiles() {
+        // 0.0, 0.001, ..., 0.999 in a scrambled order, and penalized servers.
+        let mut scores = (0..1000)
+            .map(|i| ((i * 7919) % 1000) as f64 / 1000.0)
+            .collect::<Vec<_>>();
+        scores.extend([1.0, 1.5, 1000.5]);
+        let loads = make_loads(&scores);
+
+        let percentiles = score_percentiles(&loads, &[0.5, 0.9, 0.0, 1.0]);
+        assert!((percentiles[0] - 0.5).abs() < 0.01, "{percentiles:?}");
+        assert!((percentiles[1] - 0.9).abs() < 0.01, "{percentiles:?}");
+        assert_eq!(percentiles[2], 0.0);
+        assert_eq!(percentiles[3], 0.999);
+
+        // Exact with few scores
+        let loads = make_loads(&[0.4, 0.1, 1000.2, 0.3]);
+        assert_eq!(
+            score_percentiles(&loads, &[0.0, 0.5, 1.0]),
+            [0.1, 0.3, 0.4]
+        );
+
+        assert!(score_percentiles(&make_loads(&[1000.1]), &[0.5])[0].is_nan());
+        assert!(score_percentiles(&loads, &[]).is_empty());
+    }
+
+    #[test_log::test]
+    fn test_rank_diff() {
+        let old = make_loads(&[0.1, 0.2, 0.3, 1000.5]);
+        let new = make_loads(&[0.25, 0.2, 0.3, 1000.5]);
+
+        assert_eq!(
+            rank_diff(&old, &new),
+            vec![
+                RankChange {
+                    index: 0,
+                    old_rank: 0,
+                    new_rank: 1,
+                    score_delta: 0.25 - 0.1,
+                },
+                RankChange {
+                    index: 1,
+                    old_rank: 1,
+                    new_rank: 0,
+                    score_delta: 0.0,
+                },
+            ]
+        );
+
+        assert!(rank_diff(&old, &old).is_empty());
+    }
+
+    #[test_log::test]
+    fn test_score_gap_to_best() {
+        let loads = make_loads(&[0.3, 1000.1, 0.1, 0.3]);
+
+        assert!((score_gap_to_best(&loads, 0) - 0.2).abs() < 1e-9);
+        assert!((score_gap_to_best(&loads, 2) + 0.2).abs() < 1e-9);
+        assert!((score_gap_to_best(&loads, 1) - 1000.0).abs() < 1e-9);
+
+        let tied = make_loads(&[0.1, 0.1]);
+        assert_eq!(score_gap_to_best(&tied, 1), 0.0);
+        assert_eq!(score_gap_to_best(&make_loads(&[0.5]), 0), 0.0);
+    }
+
+    #[test_log::test]
+    fn test_loads_digest() {
+        let loads = make_loads(&[0.1, 0.2, 1000.5]);
+        assert_eq!(loads[0].score_fixed(), 100_000_000);
+        assert_eq!(loads[2].score_fixed(), 1_000_500_000_000);
+        assert_eq!(make_loads(&[f64::MAX])[0].score_fixed(), i64::MAX);
+
+        // Pinned, so that a change of the digest across platforms or
+        // versions is caught.
+        assert_eq!(loads_digest(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(loads_digest(&loads), 0xd48f_aaa8_4214_5b21);
+
+        // Differences below the fixed-point precision are ignored
+        let close = make_loads(&[0.1 + 1e-12, 0.2, 1000.5]);
+        assert_eq!(loads_digest(&close), loads_digest(&loads));
+
+        let mut changed = loads.clone();
+        changed[1].is_autoconnectable = !changed[1].is_autoconnectable;
+        assert_ne!(loads_digest(&changed), loads_digest(&loads));
+        assert_ne!(
+            loads_digest(&make_loads(&[0.2, 0.1, 1000.5])),
+            loads_digest(&loads)
+        );
+    }
+
+    #[test_log::test]
+    fn test_penalty() {
+        let load = |score, penalty| Load {
+            score: Score(score),
+            penalty,
+            ..Default::default()
+        };
+
+        assert!(!load(0.25, 0.0).is_penalized());
+        assert_eq!(load(0.25, 0.0).base_score(), 0.25);
+
+        // The worst base score is not penalized.
+        assert!(!load(1.0, 0.0).is_penalized());
+
+        // The best base score with a penalty of 1.0
+        assert!(load(1.0, 1.0).is_penalized());
+        assert_eq!(load(1.0, 1.0).base_score(), 0.0);
+
+        // The worst base score with a penalty of 1.0
+        assert_eq!(load(2.0, 1.0).base_score(), 1.0);
+
+        // A penalty that isn't a whole number, e.g. from the back end
+        assert!(load(0.9, 0.4).is_penalized());
+        assert!((load(0.9, 0.4).base_score() - 0.5).abs() < 1e-9);
+    }
+
+    #[test_log::test]
+    fn test_backend_penalty() -> Result<(), Box<dyn std::error::Error>> {
+        let logicals = [crate::Logical {
+            status_reference: crate::StatusReference {
+                penalty: 0.4,
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 10_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Enabled, visible
+        ];
+
+        let mut loads = vec![Load::default()];
+        crate::compute_loads(
+            &mut loads,
+            &logicals,
+            &status_file,
+            &None,
+            &None,
+        )?;
+
+        assert!(loads[0].is_penalized());
+        assert_eq!(loads[0].penalty, 0.4);
+        assert!((0.0..=1.0).contains(&loads[0].base_score()));
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_quality() {
+        let load = |score| Load {
+            is_enabled: true,
+            is_visible: true,
+            score: Score(score),
+            ..Default::default()
+        };
+
+        assert_eq!(load(0.1).quality(), Quality::High);
+        assert_eq!(load(0.7).quality(), Quality::Medium);
+        assert_eq!(load(1.7).quality(), Quality::Low);
+        assert_eq!(load(1000.1).quality(), Quality::Unavailable);
+        assert_eq!(
+            Load {
+                is_visible: false,
+                ..load(0.1)
+            }
+            .quality(),
+            Quality::Unavailable
+        );
+
+        let config = crate::ScoringConfig {
+            quality_thresholds: crate::QualityThresholds {
+                high: 0.05,
+                medium: 0.5,
+            },
+            ..Default::default()
+        };
+        assert_eq!(load(0.1).quality_with_config(&config), Quality::Medium);
+        assert_eq!(load(0.7).quality_with_config(&config), Quality::Low);
+    }
+
+    #[test_log::test]
+    fn test_load_bucket() {
+        let buckets = [30, 70];
+        let bucket = |load| {
+            Load {
+                load,
+                ..Default::default()
+            }
+            .load_bucket(&buckets)
+        };
+
+        assert_eq!(bucket(0), 0);
+        assert_eq!(bucket(29), 0);
+        assert_eq!(bucket(30), 1);
+        assert_eq!(bucket(69), 1);
+        assert_eq!(bucket(70), 2);
+        assert_eq!(bucket(100), 2);
+        assert_eq!(Load::default().load_bucket(&[]), 0);
+    }
+
+    #[test_log::test]
+    fn test_to_wire_le() {
+        let wire = Load {
+            is_enabled: true,
+            is_autoconnectable: true,
+            load: 42,
+            // Enabled, autoconnectable and an unknown bit
+            status: 0x85,
+            score: Score(1.5),
+            distance_km: -2.0,
+            display_fraction: 0.25,
+            penalty: 1.0,
+            ..Default::default()
+        }
+        .to_wire_le();
+
+        assert_eq!(wire[..5], [1, 0, 1, 42, 0x85]);
+        assert_eq!(wire[5..13], [0, 0, 0, 0, 0, 0, 0xf8, 0x3f]);
+        assert_eq!(wire[13..21], [0, 0, 0, 0, 0, 0, 0, 0xc0]);
+        assert_eq!(wire[21..29], [0, 0, 0, 0, 0, 0, 0xd0, 0x3f]);
+        assert_eq!(wire[29..37], [0, 0, 0, 0, 0, 0, 0xf0, 0x3f]);
+    }
+
+    #[test_log::test]
+    fn test_iter_by_score() {
+        let logicals = (0..4)
+            .map(|index| crate::Logical {
+                status_reference: crate::StatusReference {
+                    index: crate::ServerIndex(index),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let loads = make_loads(&[0.3, 1000.1, 0.1, 0.3]);
+
+        let order = iter_by_score(&logicals, &loads)
+            .map(|(logical, load)| {
+                (logical.status_reference.index.0, load.score.value())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(order, vec![(2, 0.1), (0, 0.3), (3, 0.3), (1, 1000.1)]);
+        assert_eq!(iter_by_score(&logicals[..2], &loads).count(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_log::test]
+    fn test_write_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let logicals = [crate::Logical {
+            exit_location: crate::Location {
+                latitude: 46.5,
+                longitude: 6.25,
+            },
+            exit_country: crate::CountryCode::try_from(b"CH")?,
+            ..Default::default()
+        }];
+        let loads = [Load {
+            is_enabled: true,
+            is_visible: true,
+            load: 42,
+            score: Score(0.75),
+            ..Default::default()
+        }];
+
+        let mut csv = Vec::new();
+        write_csv(&loads, &logicals, &mut csv)?;
+
+        let csv = String::from_utf8(csv)?;
+        let mut lines = csv.lines();
+        assert!(lines.next().is_some_and(|header| header.starts_with(
+            "Index,ExitCountry,Latitude,Longitude,IsEnabled,IsVisible,Load,Score"
+        )));
+        assert!(lines.next().is_some_and(
+            |row| row.starts_with("0,CH,46.5,6.25,true,true,42,0.75")
+        ));
+        assert_eq!(lines.next(), None);
+
+        assert!(write_csv(&loads, &[], &mut Vec::new()).is_err());
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_ranks_ties_are_broken_by_index() {
+        assert_eq!(ranks(&make_loads(&[0.5, 0.1, 0.5, 0.1])), [2, 0, 3, 1]);
+
+        let loads = make_loads(&[0.3, f64::NAN, 1000.1, 0.2]);
+        let ranks = ranks(&loads);
+        assert_eq!(ranks, [1, 3, 2, 0]);
+        for (rank, index) in score_order(&loads).into_iter().enumerate() {
+            assert_eq!(ranks[index] as usize, rank);
+        }
+    }
+}
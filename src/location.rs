@@ -15,3 +15,41 @@ pub struct Location {
     #[cfg_attr(feature = "serde", serde(rename = "Longitude"))]
     pub longitude: f32,
 }
+
+impl Location {
+    /// Returns whether the location can be used for scoring, i.e. both
+    /// coordinates are finite, the latitude is in -90.0..=90.0 and the
+    /// longitude in -180.0..=180.0.
+    ///
+    /// `compute_loads` returns `Error::InvalidUserLocation` for a user
+    /// location that isn't, so a doubtful location (e.g. from geo-IP) can be
+    /// checked beforehand and passed as `None` instead.
+    pub fn is_scoreable(&self) -> bool {
+        self.latitude.is_finite()
+            && self.longitude.is_finite()
+            && (-90.0..=90.0).contains(&self.latitude)
+            && (-180.0..=180.0).contains(&self.longitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_is_scoreable() {
+        let location = |latitude, longitude| Location {
+            latitude,
+            longitude,
+        };
+
+        assert!(location(46.2044, 6.1432).is_scoreable());
+        assert!(location(-90.0, 180.0).is_scoreable());
+        assert!(location(90.0, -180.0).is_scoreable());
+
+        assert!(!location(90.5, 0.0).is_scoreable());
+        assert!(!location(0.0, -180.5).is_scoreable());
+        assert!(!location(f32::NAN, 0.0).is_scoreable());
+        assert!(!location(0.0, f32::INFINITY).is_scoreable());
+    }
+}
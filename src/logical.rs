@@ -1,9 +1,48 @@
 // -----------------------------------------------------------------------------
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
-use crate::country_code::CountryCode;
+use crate::country_code::{CountryCode, OptionalCountryCode};
 use crate::location::Location;
 
+/// The position of a server in the binary status file.
+///
+/// Use `ServerIndex::to_usize` to index the status file, the conversion is
+/// checked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ServerIndex(pub u32);
+
+#[cfg(feature = "uniffi")]
+uniffi::custom_newtype!(ServerIndex, u32);
+
+impl ServerIndex {
+    /// Returns the index as a `usize`, or `None` if it doesn't fit in one
+    /// on this target.
+    pub fn to_usize(self) -> Option<usize> {
+        usize::try_from(self.0).ok()
+    }
+}
+
+impl From<u32> for ServerIndex {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ServerIndex> for u32 {
+    fn from(value: ServerIndex) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for ServerIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 /// Contains information necessary for calculating the
 /// server status
 ///
@@ -14,15 +53,69 @@ use crate::location::Location;
 pub struct StatusReference {
     // The position of the server in the binary status file
     #[cfg_attr(feature = "serde", serde(rename = "Index"))]
-    pub index: u32,
+    pub index: ServerIndex,
     // The penalty computed by the back end
     #[cfg_attr(feature = "serde", serde(rename = "Penalty"))]
     pub penalty: f64,
-    // Is this server expensive ? 1 for yes, 0 for no.
+    // How expensive this server is. 0 for a server that isn't expensive, 1
+    // for an expensive one, or a `Cost` packed in a byte when
+    // `ScoringConfig::tiered_cost` is set.
     #[cfg_attr(feature = "serde", serde(rename = "Cost"))]
     pub cost: u8,
 }
 
+/// The cost of a server, packed in the `StatusReference::cost` byte.
+///
+/// The lower 7 bits hold the tier and the high bit whether a free tier is
+/// available, so the legacy 0 and 1 cost bytes are tiers 0 and 1 without a
+/// free tier. The tiers are only scored with `ScoringConfig::tiered_cost`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Cost {
+    // The monetary tier of the server, 0 when it isn't expensive. The
+    // cross-country penalty of expensive servers is scaled by the tier.
+    pub tier: u8,
+    // Whether a free tier is available on the server. It doesn't change the
+    // score.
+    pub free_available: bool,
+}
+
+impl Cost {
+    /// The highest tier that fits in the cost byte.
+    pub const MAX_TIER: u8 = 0x7f;
+
+    const FREE_AVAILABLE: u8 = 0x80;
+
+    /// Unpacks a cost byte.
+    pub const fn from_byte(byte: u8) -> Self {
+        Self {
+            tier: byte & Self::MAX_TIER,
+            free_available: byte & Self::FREE_AVAILABLE != 0,
+        }
+    }
+
+    /// Packs the cost in a byte, tiers above `MAX_TIER` are saturated.
+    pub const fn to_byte(self) -> u8 {
+        let tier = if self.tier > Self::MAX_TIER {
+            Self::MAX_TIER
+        } else {
+            self.tier
+        };
+
+        if self.free_available {
+            tier | Self::FREE_AVAILABLE
+        } else {
+            tier
+        }
+    }
+}
+
+impl StatusReference {
+    /// Returns the unpacked cost of the server.
+    pub fn cost(&self) -> Cost {
+        Cost::from_byte(self.cost)
+    }
+}
+
 /// Contains server specific information obtained from /logicals
 #[derive(Debug, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "cffi", repr(C))]
@@ -40,6 +133,93 @@ pub struct Logical {
     #[cfg_attr(feature = "serde", serde(rename = "ExitLocation"))]
     pub exit_location: Location,
     // A 2 character byte array representing the country this server is in.
+    // Defaults to the "XX" placeholder country code.
     #[cfg_attr(feature = "serde", serde(rename = "ExitCountry"))]
     pub exit_country: CountryCode,
+    // The country of the entry server of secure core servers, zeroed for the
+    // other servers. Use `Logical::entry_country` to read it as an `Option`.
+    #[cfg_attr(feature = "serde", serde(rename = "EntryCountry", default))]
+    pub entry_country: OptionalCountryCode,
+}
+
+impl Logical {
+    /// Returns the country of the entry server of secure core servers, or
+    /// `None` for the other servers.
+    pub fn entry_country(&self) -> Option<CountryCode> {
+        self.entry_country.get()
+    }
+
+    /// Sets the country of the entry server of secure core servers.
+    pub fn set_entry_country(&mut self, entry_country: Option<CountryCode>) {
+        self.entry_country = entry_country.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    type AnyResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_cost() {
+        assert_eq!(Cost::from_byte(0), Cost::default());
+        assert_eq!(
+            Cost::from_byte(1),
+            Cost {
+                tier: 1,
+                free_available: false,
+            }
+        );
+        assert_eq!(
+            Cost::from_byte(0x82),
+            Cost {
+                tier: 2,
+                free_available: true,
+            }
+        );
+
+        for byte in 0..=u8::MAX {
+            assert_eq!(Cost::from_byte(byte).to_byte(), byte);
+        }
+        let cost = Cost {
+            tier: u8::MAX,
+            free_available: false,
+        };
+        assert_eq!(cost.to_byte(), Cost::MAX_TIER);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_log::test]
+    fn test_default_round_trip() -> AnyResult {
+        let logical = Logical::default();
+        let serialized = serde_json::to_string(&logical)?;
+        assert_eq!(serde_json::from_str::<Logical>(&serialized)?, logical);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_log::test]
+    fn test_entry_country() -> AnyResult {
+        let json = r#"{
+            "StatusReference": { "Index": 0, "Penalty": 0.0, "Cost": 0 },
+            "EntryLocation": { "Latitude": 0.0, "Longitude": 0.0 },
+            "ExitLocation": { "Latitude": 0.0, "Longitude": 0.0 },
+            "ExitCountry": "CH"
+        }"#;
+        assert_eq!(
+            serde_json::from_str::<Logical>(json)?.entry_country(),
+            None
+        );
+
+        let mut logical = Logical::default();
+        logical.set_entry_country(Some(CountryCode::try_from(b"IS")?));
+        let serialized = serde_json::to_string(&logical)?;
+        assert!(serialized.contains(r#""EntryCountry":"IS""#));
+        assert_eq!(serde_json::from_str::<Logical>(&serialized)?, logical);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,82 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+
+/// The score of a server, lower is better.
+///
+/// A score is an opaque total, only meant for ordering servers: it doesn't
+/// carry its components, and no range of values stands for a given penalty.
+/// Use `Load::penalty` to find out whether, and how much, a server was
+/// penalized.
+///
+/// Scores are totally ordered with `f64::total_cmp`, so they can be sorted
+/// without panicking.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "cffi", repr(transparent))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Score(pub f64);
+
+impl Score {
+    /// Returns the raw score.
+    pub fn value(self) -> f64 {
+        self.0
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl From<f64> for Score {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+// Needed by `uniffi`
+impl From<Score> for f64 {
+    fn from(value: Score) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "uniffi")]
+uniffi::custom_type!(Score, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_ordering() {
+        let mut scores =
+            [Score(1000.2), Score(f64::NAN), Score(0.3), Score(1.1)];
+        scores.sort();
+
+        assert_eq!(scores[..3], [Score(0.3), Score(1.1), Score(1000.2)]);
+        assert!(scores[3].value().is_nan());
+    }
+}
@@ -0,0 +1,170 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use std::cell::Cell;
+
+use super::compute_loads::{compute_load, resolve_user_location};
+use super::jitter;
+use super::status::{Parser, ServerStatus};
+use super::{CountryCode, Load, Location, Logical, Result, ScoringConfig};
+
+/// Scores the servers one at a time, on demand.
+///
+/// This is useful when only some of the servers are displayed, e.g. in a
+/// virtualized list. The status file is parsed once, when the scorer is
+/// built, and each call to `score` then only computes a single server.
+///
+/// The jitter of each server is drawn once per scorer, so scoring the same
+/// server several times returns the same load.
+#[derive(Debug)]
+pub struct Scorer<'a> {
+    logicals: &'a [Logical],
+    statuses: Parser<'a>,
+    user_location: Option<Location>,
+    user_country: Option<CountryCode>,
+    config: ScoringConfig,
+    jitter_key: Option<u64>,
+    parsing_error_reported: Cell<bool>,
+}
+
+impl<'a> Scorer<'a> {
+    /// Creates a scorer for the given logicals.
+    ///
+    /// Returns an error if the user location or the jitter range of the
+    /// configuration is invalid.
+    pub fn new(
+        logicals: &'a [Logical],
+        statuses: Parser<'a>,
+        user_location: Option<Location>,
+        user_country: Option<CountryCode>,
+        config: ScoringConfig,
+    ) -> Result<Self> {
+        let user_location =
+            resolve_user_location(&user_location, &user_country, &config)?;
+        config.validate()?;
+
+        Ok(Self {
+            logicals,
+            statuses,
+            user_location,
+            user_country,
+            config,
+            jitter_key: jitter::random_key(),
+            parsing_error_reported: Cell::new(false),
+        })
+    }
+
+    /// Returns the number of servers that can be scored.
+    pub fn len(&self) -> usize {
+        self.logicals.len()
+    }
+
+    /// Returns whether there are no servers to score.
+    pub fn is_empty(&self) -> bool {
+        self.logicals.is_empty()
+    }
+
+    /// Computes the load of the logical at index `i`, as `compute_loads`
+    /// would.
+    ///
+    /// # Panics
+    /// Panics if `i` is out of bounds of the logicals.
+    pub fn score(&self, i: usize) -> Load {
+        let logical = &self.logicals[i];
+
+        // Only the first parsing error is logged, as `compute_loads` does.
+        let status = self.statuses.get_for(
+            logical,
+            &ServerStatus::default(),
+            &mut |index, byte_offset, error_msg| {
+                if !self.parsing_error_reported.replace(true) {
+                    log::warn!(
+                        "Failed to parse server status at index {index} with bytes offset {byte_offset}: {error_msg}. \
+                        Using default status for this server. \
+                        Further server status parsing errors will be ignored.",
+                    );
+                }
+            },
+        );
+
+        let mut load = Load::default();
+        compute_load(
+            &mut load,
+            logical,
+            &status,
+            &self.user_location,
+            &self.user_country,
+            &self.config,
+            jitter::keyed(self.jitter_key, i, &self.config.jitter),
+        );
+
+        load
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_loads;
+    use crate::test_fixtures::{make_logicals, make_status_file};
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_scorer() -> AnyResult {
+        let servers = make_logicals(3);
+
+        let status_file = make_status_file(&[
+            (1, 10, 0.0), // Enabled, not visible
+            (3, 20, 0.0), // Enabled, visible
+            (7, 30, 0.0), // Autoconnectable
+        ]);
+
+        let user_location = Some(Location {
+            latitude: 46.2044,
+            longitude: 6.1432,
+        });
+        let scorer = Scorer::new(
+            &servers,
+            Parser::try_from(&status_file[..])?,
+            user_location.clone(),
+            None,
+            ScoringConfig::default(),
+        )?;
+
+        let mut loads = vec![Load::default(); servers.len()];
+        compute_loads(
+            &mut loads,
+            &servers,
+            &status_file,
+            &user_location,
+            &None,
+        )?;
+
+        assert_eq!(scorer.len(), 3);
+        for (i, expected) in loads.iter().enumerate() {
+            let load = scorer.score(i);
+            assert_eq!(load.load, expected.load);
+            assert_eq!(load.is_visible, expected.is_visible);
+            assert!((load.score.value() - expected.score.value()).abs() < 0.01);
+
+            // The jitter is keyed on the index
+            assert_eq!(scorer.score(i), load);
+        }
+
+        let error = Scorer::new(
+            &servers,
+            Parser::try_from(&status_file[..])?,
+            Some(Location {
+                latitude: 100.0,
+                longitude: 0.0,
+            }),
+            None,
+            ScoringConfig::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, crate::Error::InvalidUserLocation { .. }));
+
+        Ok(())
+    }
+}
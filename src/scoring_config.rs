@@ -0,0 +1,560 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use crate::compute_score::{
+    BANDWITH_DISTANCE_FACTOR, FOREIGN_EXPENSIVE_PENALTY,
+    FOREIGN_OR_SATURATED_PENALTY, UNAVAILABLE_PENALTY,
+};
+use crate::country_code::CountryCode;
+use crate::error::{Error, Result};
+use crate::jitter::JitterConfig;
+use crate::location::Location;
+use crate::logical::Cost;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Controls how the cross-country penalties are applied when the user
+/// country is unknown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnknownCountryPolicy {
+    /// Every server is assumed to be in the user's country, so no
+    /// cross-country penalty is applied.
+    #[default]
+    AssumeDomestic,
+    /// Every server is assumed to be in a different country than the user,
+    /// so the cross-country penalties are applied to all servers. This
+    /// preserves the relative order by distance and load.
+    AssumeForeign,
+}
+
+/// Controls which penalty is used when the status file carries a per-server
+/// penalty (version 2 status files) in addition to the one from /logicals.
+///
+/// When the status file has no penalty for a server, the penalty from the
+/// logical is always used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatusPenaltyPrecedence {
+    /// The penalty from the status file replaces the one from the logical.
+    /// The status file is refreshed more often, so it reflects the current
+    /// conditions better.
+    #[default]
+    StatusFile,
+    /// The penalty from the status file is ignored.
+    Logical,
+    /// Both penalties are added together.
+    Sum,
+}
+
+impl StatusPenaltyPrecedence {
+    // Returns the penalty to use for scoring.
+    pub(crate) fn resolve(
+        &self,
+        logical_penalty: f64,
+        status_file_penalty: Option<f32>,
+    ) -> f64 {
+        match (self, status_file_penalty) {
+            (_, None) | (Self::Logical, _) => logical_penalty,
+            (Self::StatusFile, Some(penalty)) => penalty as f64,
+            (Self::Sum, Some(penalty)) => logical_penalty + penalty as f64,
+        }
+    }
+}
+
+/// Controls which legs of the travel distance are used for scoring.
+///
+/// The default mode is `Legacy` when the library is built with the `legacy`
+/// feature, and `EntryFirst` otherwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DistanceMode {
+    /// The client leg goes to the server exit, followed by the internal leg
+    /// from the server entry to the server exit.
+    #[cfg_attr(feature = "legacy", default)]
+    Legacy,
+    /// The client leg goes to the server entry, followed by the internal leg
+    /// from the server entry to the server exit.
+    #[cfg_attr(not(feature = "legacy"), default)]
+    EntryFirst,
+    /// Only the client leg to the server entry is used, the internal leg of
+    /// secure core servers is ignored.
+    EntryOnly,
+    /// The client leg goes to whichever of the server entry or exit is
+    /// closer, followed by the internal leg from the server entry to the
+    /// server exit.
+    NearestEndpoint,
+}
+
+/// Controls how the distance score and the partial score of a server are
+/// combined into its base score.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CapStrategy {
+    /// The larger of the two scores, so a server is only as good as its
+    /// worst component.
+    #[default]
+    Max,
+    /// `distance_weight * distance_score + load_weight * partial_score`. The
+    /// weighted sum is clamped to 0.0..=1.0 with the jitter, weights summing
+    /// to 1.0 keep it in range.
+    WeightedSum {
+        #[cfg_attr(feature = "serde", serde(rename = "DistanceWeight"))]
+        distance_weight: f64,
+        #[cfg_attr(feature = "serde", serde(rename = "LoadWeight"))]
+        load_weight: f64,
+    },
+}
+
+impl CapStrategy {
+    // Returns the base score, before the jitter is applied.
+    pub(crate) fn combine(
+        &self,
+        distance_score: f64,
+        partial_score: f64,
+    ) -> f64 {
+        match self {
+            Self::Max => f64::max(distance_score, partial_score),
+            Self::WeightedSum {
+                distance_weight,
+                load_weight,
+            } => distance_weight * distance_score + load_weight * partial_score,
+        }
+    }
+}
+
+/// Estimates the bandwidth of a server from its travel distance, as a
+/// factor (in Mbps·km) divided by the distance.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BandwidthModel {
+    /// The same factor at every distance.
+    Single(f64),
+    /// `(max_distance_km, factor)` breakpoints, sorted by distance. The
+    /// factor of the first breakpoint whose maximum distance is at least the
+    /// travel distance is used, and the last factor past the last
+    /// breakpoint.
+    ///
+    /// The estimate is only continuous at a breakpoint when the factors on
+    /// both sides of it are the same.
+    Piecewise(Vec<(f64, f64)>),
+}
+
+impl Default for BandwidthModel {
+    fn default() -> Self {
+        Self::Single(BANDWITH_DISTANCE_FACTOR)
+    }
+}
+
+impl BandwidthModel {
+    /// Returns the factor used at the given travel distance.
+    ///
+    /// A piecewise model without breakpoints uses the default factor.
+    pub fn factor(&self, distance_km: f64) -> f64 {
+        match self {
+            Self::Single(factor) => *factor,
+            Self::Piecewise(breakpoints) => breakpoints
+                .iter()
+                .find(|(max_distance_km, _)| distance_km <= *max_distance_km)
+                .or(breakpoints.last())
+                .map_or(BANDWITH_DISTANCE_FACTOR, |(_, factor)| *factor),
+        }
+    }
+}
+
+/// Maps an estimated bandwidth, in Mbps, to a distance score in the range
+/// 0.0..=1.0, lower is better.
+///
+/// Configurations holding the same function (not merely an equivalent one)
+/// are equal.
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct NormalizeFn(pub Arc<dyn Fn(f64) -> f64 + Send + Sync>);
+
+impl NormalizeFn {
+    pub fn new(normalize: impl Fn(f64) -> f64 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(normalize))
+    }
+}
+
+impl std::ops::Deref for NormalizeFn {
+    type Target = dyn Fn(f64) -> f64 + Send + Sync;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl std::fmt::Debug for NormalizeFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NormalizeFn(..)")
+    }
+}
+
+impl PartialEq for NormalizeFn {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Score thresholds used to categorize servers by `Load::quality`.
+///
+/// Servers that are disabled, hidden or unavailable are always
+/// `Quality::Unavailable`, the thresholds only apply to the other ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityThresholds {
+    /// Servers with a score below this are `Quality::High`.
+    #[cfg_attr(feature = "serde", serde(rename = "High"))]
+    pub high: f64,
+    /// Servers with a score below this (and not `Quality::High`) are
+    /// `Quality::Medium`, the others are `Quality::Low`.
+    #[cfg_attr(feature = "serde", serde(rename = "Medium"))]
+    pub medium: f64,
+}
+
+impl Default for QualityThresholds {
+    // Unpenalized servers have a score in the range 0.0..=1.0, penalized
+    // ones are always `Quality::Low`.
+    fn default() -> Self {
+        Self {
+            high: 0.5,
+            medium: 1.0,
+        }
+    }
+}
+
+/// Runtime configuration of the scoring.
+///
+/// The default configuration matches the scoring done by `compute_loads`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScoringConfig {
+    /// How to apply the cross-country penalties when the user country is
+    /// unknown.
+    #[cfg_attr(feature = "serde", serde(rename = "UnknownCountryPolicy"))]
+    pub unknown_country_policy: UnknownCountryPolicy,
+    /// Whether `compute_loads` fills in `Load::display_fraction`.
+    #[cfg_attr(feature = "serde", serde(rename = "ComputeDisplayFraction"))]
+    pub compute_display_fraction: bool,
+    /// Which penalty to use when the status file carries per-server
+    /// penalties.
+    #[cfg_attr(feature = "serde", serde(rename = "StatusPenaltyPrecedence"))]
+    pub status_penalty_precedence: StatusPenaltyPrecedence,
+    /// The country whose servers get `preferred_country_bonus` subtracted
+    /// from their score, even when the user is in another country.
+    #[cfg_attr(feature = "serde", serde(rename = "PreferredCountry"))]
+    pub preferred_country: Option<CountryCode>,
+    /// How much the score of servers in `preferred_country` is improved.
+    /// It only applies to the base score, which is clamped to 0.0, so it
+    /// never moves a server out of its penalty tier.
+    #[cfg_attr(feature = "serde", serde(rename = "PreferredCountryBonus"))]
+    pub preferred_country_bonus: f64,
+    /// The thresholds used by `Load::quality_with_config`.
+    #[cfg_attr(feature = "serde", serde(rename = "QualityThresholds"))]
+    pub quality_thresholds: QualityThresholds,
+    /// Rounds `Load::load` to the nearest multiple of this value, so that
+    /// near-identical loads are displayed the same. 0 and 1 disable the
+    /// rounding. It doesn't affect the score.
+    #[cfg_attr(feature = "serde", serde(rename = "LoadDisplayRounding"))]
+    pub load_display_rounding: u8,
+    /// Which legs make up the travel distance.
+    #[cfg_attr(feature = "serde", serde(rename = "DistanceMode"))]
+    pub distance_mode: DistanceMode,
+    /// Scores the servers by their travel distance only, as if their partial
+    /// score was 0.0. The partial score is still used for the saturation
+    /// penalty.
+    #[cfg_attr(feature = "serde", serde(rename = "IgnorePartialScore"))]
+    pub ignore_partial_score: bool,
+    /// Multiplies the score of the servers by the weight of their exit
+    /// country, 1.0 for the countries without a weight. Like
+    /// `preferred_country_bonus`, it only applies to the base score, so it
+    /// never moves a server out of its penalty tier.
+    #[cfg_attr(feature = "serde", serde(rename = "CountryWeights"))]
+    pub country_weights: HashMap<CountryCode, f64>,
+    /// Multiplies the internal leg of the travel distance (from the server
+    /// entry to the server exit) before it is added to the client leg. It
+    /// only affects the score, `Load::distance_km` is the actual distance.
+    #[cfg_attr(feature = "serde", serde(rename = "InternalLegWeight"))]
+    pub internal_leg_weight: f64,
+    /// Uses the centroid of the user country as the user location when the
+    /// user location is unknown, so that the servers are still scored by
+    /// distance. Without it, every server gets the same distance score.
+    #[cfg_attr(feature = "serde", serde(rename = "CountryCentroidFallback"))]
+    pub country_centroid_fallback: bool,
+    /// How the bandwidth of a server is estimated from its travel distance.
+    #[cfg_attr(feature = "serde", serde(rename = "BandwidthModel"))]
+    pub bandwidth_model: BandwidthModel,
+    /// Clamps the final score, penalties included, to the `(min, max)`
+    /// range, for storing it in a bounded field. Clamping collapses the
+    /// scores beyond the range, so heavily penalized servers can't be
+    /// ordered among themselves anymore. `None` leaves the score unbounded.
+    #[cfg_attr(feature = "serde", serde(rename = "FinalScoreClamp"))]
+    pub final_score_clamp: Option<(f64, f64)>,
+    /// Replaces the linear mapping of the estimated bandwidth to the
+    /// distance score, `(10000 - bandwidth_mbps) / 10000`.
+    ///
+    /// Functions can't be serialized, so it is skipped when the
+    /// configuration is serialized and `None` once deserialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub normalize_fn: Option<NormalizeFn>,
+    /// The range the jitter is sampled from. It must be valid, see
+    /// `JitterConfig::validate`.
+    #[cfg_attr(feature = "serde", serde(rename = "Jitter"))]
+    pub jitter: JitterConfig,
+    /// How the distance score and the partial score are combined.
+    #[cfg_attr(feature = "serde", serde(rename = "CapStrategy"))]
+    pub cap_strategy: CapStrategy,
+    /// Scales the cross-country penalty of expensive servers by the tier of
+    /// their `Cost`. Without it, the cost byte is read as the legacy flag,
+    /// and any non-zero cost gets the penalty of tier 1.
+    #[cfg_attr(feature = "serde", serde(rename = "TieredCost"))]
+    pub tiered_cost: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            unknown_country_policy: UnknownCountryPolicy::default(),
+            compute_display_fraction: false,
+            status_penalty_precedence: StatusPenaltyPrecedence::default(),
+            preferred_country: None,
+            preferred_country_bonus: 0.0,
+            quality_thresholds: QualityThresholds::default(),
+            load_display_rounding: 0,
+            distance_mode: DistanceMode::default(),
+            ignore_partial_score: false,
+            country_weights: HashMap::new(),
+            internal_leg_weight: 1.0,
+            country_centroid_fallback: false,
+            bandwidth_model: BandwidthModel::default(),
+            final_score_clamp: None,
+            normalize_fn: None,
+            jitter: JitterConfig::default(),
+            cap_strategy: CapStrategy::default(),
+            tiered_cost: false,
+        }
+    }
+}
+
+impl ScoringConfig {
+    /// Returns `Error::InvalidJitterRange` if the jitter range is invalid,
+    /// `Error::InvalidPreferredCountryBonus` unless the preferred country
+    /// bonus is finite and not negative, and `Error::InvalidCountryWeight`
+    /// unless every country weight is finite and not negative.
+    pub fn validate(&self) -> Result<()> {
+        self.jitter.validate()?;
+
+        let bonus = self.preferred_country_bonus;
+        if !bonus.is_finite() || bonus < 0.0 {
+            return Err(Error::InvalidPreferredCountryBonus { bonus });
+        }
+
+        for (country, &weight) in &self.country_weights {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(Error::InvalidCountryWeight {
+                    country: country.to_string(),
+                    weight,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the minimum and maximum scores that can be computed with this
+    /// configuration.
+    ///
+    /// The penalty computed by the back end is not bounded, so the maximum
+    /// assumes it is 0.0. A score above the maximum therefore comes from the
+    /// back end penalty.
+    pub fn score_bounds(&self) -> (f64, f64) {
+        // The base score is clamped to 0.0..=1.0 after the jitter is applied.
+        let (min_base_score, max_base_score) = (0.0, 1.0);
+
+        let max_tier = if self.tiered_cost { Cost::MAX_TIER } else { 1 };
+        let max_penalty = UNAVAILABLE_PENALTY
+            + FOREIGN_OR_SATURATED_PENALTY
+            + FOREIGN_EXPENSIVE_PENALTY * f64::from(max_tier);
+
+        (
+            self.clamp_score(min_base_score),
+            self.clamp_score(max_base_score + max_penalty),
+        )
+    }
+
+    /// Clamps the score to `final_score_clamp`, when set.
+    pub(crate) fn clamp_score(&self, score: f64) -> f64 {
+        match self.final_score_clamp {
+            // Unlike `f64::clamp`, doesn't panic when min > max
+            Some((min, max)) => score.max(min).min(max),
+            None => score,
+        }
+    }
+
+    /// Returns the user location, or the centroid of the user country when
+    /// it is unknown and `country_centroid_fallback` is set.
+    pub(crate) fn user_location(
+        &self,
+        user_location: &Option<Location>,
+        user_country: &Option<CountryCode>,
+    ) -> Option<Location> {
+        match (user_location, user_country) {
+            (Some(location), _) => Some(location.clone()),
+            (None, Some(country)) if self.country_centroid_fallback => {
+                country.centroid()
+            }
+            (None, _) => None,
+        }
+    }
+
+    /// Rounds the load to the nearest multiple of `load_display_rounding`,
+    /// halves rounding up.
+    pub(crate) fn round_load(&self, load: u8) -> u8 {
+        let step = u16::from(self.load_display_rounding);
+        if step <= 1 {
+            return load;
+        }
+
+        let rounded = (u16::from(load) + step / 2) / step * step;
+        u8::try_from(rounded).unwrap_or(u8::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_status_penalty_precedence() {
+        use StatusPenaltyPrecedence::*;
+
+        for precedence in [StatusFile, Logical, Sum] {
+            assert_eq!(precedence.resolve(2.0, None), 2.0);
+        }
+
+        assert_eq!(StatusFile.resolve(2.0, Some(0.5)), 0.5);
+        assert_eq!(Logical.resolve(2.0, Some(0.5)), 2.0);
+        assert_eq!(Sum.resolve(2.0, Some(0.5)), 2.5);
+    }
+
+    #[test_log::test]
+    fn test_round_load() {
+        let config = |load_display_rounding| ScoringConfig {
+            load_display_rounding,
+            ..Default::default()
+        };
+
+        assert_eq!(config(0).round_load(37), 37);
+        assert_eq!(config(1).round_load(37), 37);
+        assert_eq!(config(5).round_load(37), 35);
+        assert_eq!(config(5).round_load(38), 40);
+        assert_eq!(config(10).round_load(95), 100);
+        assert_eq!(config(170).round_load(255), u8::MAX);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_log::test]
+    fn test_serde() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config = ScoringConfig {
+            unknown_country_policy: UnknownCountryPolicy::AssumeForeign,
+            status_penalty_precedence: StatusPenaltyPrecedence::Sum,
+            preferred_country: Some(CountryCode::try_from("CH")?),
+            preferred_country_bonus: 0.25,
+            distance_mode: DistanceMode::NearestEndpoint,
+            country_weights: HashMap::from([(
+                CountryCode::try_from("FR")?,
+                1.5,
+            )]),
+            bandwidth_model: BandwidthModel::Piecewise(vec![
+                (500.0, 2_000_000.0),
+                (5000.0, 1_000_000.0),
+            ]),
+            final_score_clamp: Some((0.0, 100.0)),
+            ..Default::default()
+        };
+
+        let serialized = serde_json::to_string(&config)?;
+        assert_eq!(serde_json::from_str::<ScoringConfig>(&serialized)?, config);
+
+        // The missing fields keep their default value
+        assert_eq!(
+            serde_json::from_str::<ScoringConfig>(
+                r#"{"DistanceMode": "EntryOnly"}"#
+            )?,
+            ScoringConfig {
+                distance_mode: DistanceMode::EntryOnly,
+                ..Default::default()
+            }
+        );
+
+        // The normalization function isn't serialized
+        let config = ScoringConfig {
+            normalize_fn: Some(NormalizeFn::new(|bandwidth| bandwidth)),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_string(&config)?;
+        assert_eq!(
+            serde_json::from_str::<ScoringConfig>(&serialized)?,
+            ScoringConfig::default()
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_validate() {
+        assert!(ScoringConfig::default().validate().is_ok());
+
+        for bonus in [-0.1, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                ScoringConfig {
+                    preferred_country_bonus: bonus,
+                    ..Default::default()
+                }
+                .validate(),
+                Err(Error::InvalidPreferredCountryBonus { .. })
+            ));
+        }
+
+        for weight in [-1.0, f64::NAN, f64::NEG_INFINITY] {
+            assert!(matches!(
+                ScoringConfig {
+                    country_weights: HashMap::from([(
+                        CountryCode::try_from("FR").unwrap(),
+                        weight,
+                    )]),
+                    ..Default::default()
+                }
+                .validate(),
+                Err(Error::InvalidCountryWeight { .. })
+            ));
+        }
+
+        assert!(matches!(
+            ScoringConfig {
+                jitter: JitterConfig {
+                    low: 1.0,
+                    high: 0.0,
+                },
+                ..Default::default()
+            }
+            .validate(),
+            Err(Error::InvalidJitterRange { .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn test_score_bounds() {
+        assert_eq!(ScoringConfig::default().score_bounds(), (0.0, 1005.0));
+        assert_eq!(
+            ScoringConfig {
+                tiered_cost: true,
+                ..Default::default()
+            }
+            .score_bounds(),
+            (0.0, 1383.0)
+        );
+    }
+}
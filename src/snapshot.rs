@@ -0,0 +1,134 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use super::compute_loads::compute_loads_impl;
+use super::jitter;
+use super::{CountryCode, Load, Location, Logical, Result, ScoringConfig};
+
+/// Captures all the inputs of `compute_loads`, and the scoring configuration,
+/// so that a scoring run can be reproduced from a single serialized file.
+///
+#[derive(
+    Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize,
+)]
+pub struct ScoringSnapshot {
+    #[serde(rename = "Logicals")]
+    pub logicals: Vec<Logical>,
+    #[serde(rename = "StatusFile")]
+    pub status_file: Vec<u8>,
+    #[serde(rename = "UserLocation")]
+    pub user_location: Option<Location>,
+    #[serde(rename = "UserCountry")]
+    pub user_country: Option<CountryCode>,
+    // The seed of the jitter generator. When absent the jitter is random and
+    // the replayed scores will differ slightly from run to run.
+    #[serde(rename = "Seed")]
+    pub seed: Option<u64>,
+    // The configuration of the scoring. Captures from before it was recorded
+    // use the default configuration.
+    #[serde(rename = "Config", default)]
+    pub config: ScoringConfig,
+}
+
+impl ScoringSnapshot {
+    /// Re-runs `compute_loads` on the captured inputs.
+    pub fn replay(&self) -> Result<Vec<Load>> {
+        let mut loads = vec![Load::default(); self.logicals.len()];
+
+        match self.seed {
+            Some(seed) => compute_loads_impl(
+                &mut loads,
+                &self.logicals,
+                &self.status_file,
+                &self.user_location,
+                &self.user_country,
+                &self.config,
+                &mut jitter::seeded_generator(seed, &self.config.jitter)?,
+            ),
+            None => compute_loads_impl(
+                &mut loads,
+                &self.logicals,
+                &self.status_file,
+                &self.user_location,
+                &self.user_country,
+                &self.config,
+                &mut jitter::generator_with(&self.config.jitter)?,
+            ),
+        }?;
+
+        Ok(loads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{make_logical, make_status_file};
+    use crate::DistanceMode;
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    fn make_snapshot(
+    ) -> std::result::Result<ScoringSnapshot, Box<dyn std::error::Error>> {
+        let country = CountryCode::try_from(b"CH")?;
+        let logicals = (0..4)
+            .map(|index| Logical {
+                exit_location: Location {
+                    latitude: 46.2044 + index as f32, // Around Geneva
+                    longitude: 6.1432,
+                },
+                entry_location: Location {
+                    latitude: 46.2044 + index as f32,
+                    longitude: 6.1432,
+                },
+                exit_country: country,
+                ..make_logical(index)
+            })
+            .collect();
+
+        let status_file = make_status_file(&[
+            (3, 10, 0.5),
+            (3, 20, 0.5),
+            (3, 30, 0.5),
+            (3, 40, 0.5),
+        ]);
+
+        Ok(ScoringSnapshot {
+            logicals,
+            status_file,
+            user_location: Some(Location {
+                latitude: 47.36667, // Zurich
+                longitude: 8.55,
+            }),
+            user_country: Some(country),
+            seed: Some(42),
+            config: ScoringConfig {
+                distance_mode: DistanceMode::Legacy,
+                internal_leg_weight: 0.5,
+                ..Default::default()
+            },
+        })
+    }
+
+    #[test_log::test]
+    fn test_replay_is_reproducible() -> AnyResult {
+        let snapshot = make_snapshot()?;
+
+        let serialized = serde_json::to_string(&snapshot)?;
+        let deserialized: ScoringSnapshot = serde_json::from_str(&serialized)?;
+        assert_eq!(deserialized, snapshot);
+
+        assert_eq!(snapshot.replay()?, deserialized.replay()?);
+
+        // Captures without a configuration replay with the default one
+        let mut value: serde_json::Value = serde_json::from_str(&serialized)?;
+        value
+            .as_object_mut()
+            .ok_or("Snapshot isn't an object")?
+            .remove("Config");
+        let deserialized: ScoringSnapshot = serde_json::from_value(value)?;
+        assert_eq!(deserialized.config, ScoringConfig::default());
+
+        Ok(())
+    }
+}
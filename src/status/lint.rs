@@ -0,0 +1,169 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use super::parser::Parser;
+use super::version::Version;
+use crate::compute_score::{
+    STATUS_AUTOCONNECTABLE, STATUS_ENABLED, STATUS_VISIBLE,
+};
+use crate::error::*;
+
+/// The number of servers of a status file with each status flag set.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StatusCounts {
+    pub enabled: usize,
+    pub visible: usize,
+    pub autoconnectable: usize,
+}
+
+/// What `lint_status_file` found in a status file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusFileReport {
+    pub version: Version,
+    // The number of server records, valid or not.
+    pub record_count: usize,
+    // The index of each invalid record, and why it is invalid.
+    pub invalid_records: Vec<(usize, String)>,
+    // The flags of every record, invalid ones included.
+    pub counts: StatusCounts,
+}
+
+/// The indices of the records of a status file in each state, see
+/// `Parser::partition`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Partitioned {
+    pub enabled: Vec<usize>,
+    pub disabled: Vec<usize>,
+    pub hidden: Vec<usize>,
+    pub autoconnectable: Vec<usize>,
+    // The records that can't be parsed, they are in no other list.
+    pub invalid: Vec<usize>,
+}
+
+impl Parser<'_> {
+    /// Returns the indices of the records in each state, in a single pass.
+    ///
+    /// A record is in every list matching its flags, e.g. an enabled and
+    /// hidden server is in both `enabled` and `hidden`. Unlike the counts of
+    /// `lint_status_file`, invalid records are only listed in `invalid`.
+    ///
+    pub fn partition(&self) -> Partitioned {
+        let mut partitioned = Partitioned::default();
+
+        for (index, status) in self.to_vec_validated().into_iter().enumerate() {
+            let Ok(status) = status else {
+                partitioned.invalid.push(index);
+                continue;
+            };
+
+            if status.status & STATUS_ENABLED != 0 {
+                partitioned.enabled.push(index);
+            } else {
+                partitioned.disabled.push(index);
+            }
+            if status.status & STATUS_VISIBLE == 0 {
+                partitioned.hidden.push(index);
+            }
+            if status.status & STATUS_AUTOCONNECTABLE != 0 {
+                partitioned.autoconnectable.push(index);
+            }
+        }
+
+        partitioned
+    }
+}
+
+/// Parses a status file and validates each of its records, without scoring
+/// any server.
+///
+/// Returns an error if the file itself can't be parsed (e.g. an unknown
+/// version or a truncated record), the invalid records are listed in the
+/// report instead.
+pub fn lint_status_file(bytes: &[u8]) -> Result<StatusFileReport> {
+    let statuses = Parser::try_from(bytes)?;
+
+    let invalid_records = statuses
+        .to_vec_validated()
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, status)| {
+            status.err().map(|error| (index, error.to_string()))
+        })
+        .collect();
+
+    let mut counts = StatusCounts::default();
+    for status in statuses.iter_status_bytes() {
+        counts.enabled += usize::from(status & STATUS_ENABLED != 0);
+        counts.visible += usize::from(status & STATUS_VISIBLE != 0);
+        counts.autoconnectable +=
+            usize::from(status & STATUS_AUTOCONNECTABLE != 0);
+    }
+
+    Ok(StatusFileReport {
+        version: statuses.version(),
+        record_count: statuses.len(),
+        invalid_records,
+        counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_lint_status_file() -> AnyResult {
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            7_u8, 10_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Autoconnectable
+            3_u8, 150_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Invalid load
+            2_u8, 30_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Visible only
+        ];
+
+        let report = lint_status_file(&status_file)?;
+        assert_eq!(report.version, Version(1));
+        assert_eq!(report.record_count, 3);
+        assert_eq!(report.invalid_records.len(), 1);
+        assert_eq!(report.invalid_records[0].0, 1);
+        assert_eq!(
+            report.counts,
+            StatusCounts {
+                enabled: 2,
+                visible: 3,
+                autoconnectable: 1,
+            }
+        );
+
+        assert!(
+            lint_status_file(&status_file[..status_file.len() - 1]).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_partition() -> AnyResult {
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            7_u8, 10_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Autoconnectable
+            3_u8, 150_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Invalid load
+            1_u8, 30_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Enabled, hidden
+            2_u8, 30_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Disabled, visible
+        ];
+
+        assert_eq!(
+            Parser::try_from(&status_file[..])?.partition(),
+            Partitioned {
+                enabled: vec![0, 2],
+                disabled: vec![3],
+                hidden: vec![2],
+                autoconnectable: vec![0],
+                invalid: vec![1],
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use std::fs::File;
+use std::path::Path;
+
+use super::parser::Parser;
+use crate::error::*;
+
+/// A status file mapped read-only in memory.
+///
+/// The file is parsed in place, without being copied. The status file must
+/// be replaced atomically (e.g. by renaming a new file over it): a mapped
+/// file that is truncated by another process can't be read anymore, which
+/// `MmapParser::parser` detects.
+#[derive(Debug)]
+pub struct MmapParser {
+    file: File,
+    mmap: memmap2::Mmap,
+}
+
+impl MmapParser {
+    /// Returns a parser over the mapped status file.
+    ///
+    /// Returns an error if the file is now shorter than the mapping, as the
+    /// records past its new end can't be read.
+    ///
+    pub fn parser(&self) -> Result<Parser<'_>> {
+        let file_len = self.file.metadata().map_err(io_error)?.len();
+        if file_len < self.mmap.len() as u64 {
+            return Err(Error::ParserError(format!(
+                "Status file shrank from {} to {file_len} bytes while mapped",
+                self.mmap.len()
+            )));
+        }
+
+        Parser::try_from(&self.mmap[..])
+    }
+}
+
+impl Parser<'_> {
+    /// Maps the status file at the given path read-only and checks that it
+    /// can be parsed.
+    ///
+    /// The returned `MmapParser` gives access to the usual `Parser` API
+    /// through `MmapParser::parser`.
+    ///
+    pub fn try_from_path(path: &Path) -> Result<MmapParser> {
+        let file = File::open(path).map_err(io_error)?;
+
+        // Mapping an empty file fails on some platforms, such files are
+        // rejected by the parser anyway.
+        if file.metadata().map_err(io_error)?.len() == 0 {
+            Parser::try_from(&[][..])?;
+        }
+
+        // The mapping is read-only, and the length of the file is checked
+        // against the mapping before each use.
+        // nosem: rust.lang.security.unsafe-usage.unsafe-usage
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+
+        let mmap_parser = MmapParser { file, mmap };
+        mmap_parser.parser()?;
+
+        Ok(mmap_parser)
+    }
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::IoError(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::ServerStatus;
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_try_from_path() -> AnyResult {
+        let path = std::env::temp_dir().join(format!(
+            "proton_vpn_binary_status_mmap_{}",
+            std::process::id()
+        ));
+
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 10_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 0
+            3_u8, 20_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 1
+        ];
+        std::fs::write(&path, status_file)?;
+
+        let mmap_parser = Parser::try_from_path(&path)?;
+        let parser = mmap_parser.parser()?;
+        assert_eq!(parser.len(), 2);
+        assert_eq!(
+            parser.iter().map(|status| status.load).collect::<Vec<_>>(),
+            [10, 20]
+        );
+        assert_eq!(
+            parser
+                .get(1, &ServerStatus::default(), &mut |_, _, _| {})
+                .load,
+            20
+        );
+
+        // Truncating the mapped file invalidates the mapping
+        File::options()
+            .write(true)
+            .open(&path)?
+            .set_len(status_file.len() as u64 - 6)?;
+        assert!(matches!(mmap_parser.parser(), Err(Error::ParserError(_))));
+        drop(mmap_parser);
+
+        std::fs::write(&path, [])?;
+        assert!(matches!(
+            Parser::try_from_path(&path),
+            Err(Error::ParserError(_))
+        ));
+
+        std::fs::remove_file(&path)?;
+        assert!(matches!(
+            Parser::try_from_path(&path),
+            Err(Error::IoError(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -2,8 +2,21 @@
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
 
+mod lint;
+#[cfg(feature = "mmap")]
+mod mmap_parser;
+mod parse_error_collector;
 mod parser;
 mod server_status;
+mod version;
+mod writer;
 
+pub use lint::{lint_status_file, Partitioned, StatusCounts, StatusFileReport};
+#[cfg(feature = "mmap")]
+pub use mmap_parser::MmapParser;
+pub use parse_error_collector::{DefaultedServer, ParseErrorCollector};
 pub use parser::Parser;
-pub use server_status::ServerStatus;
+pub(crate) use server_status::validate_server;
+pub use server_status::{ServerStatus, MAX_LOAD, PARTIAL_SCORE_RANGE};
+pub use version::Version;
+pub use writer::{merge_status_prefer_enabled, status_file_from_loads};
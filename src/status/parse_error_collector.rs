@@ -0,0 +1,113 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use std::collections::BTreeMap;
+
+use crate::logical::ServerIndex;
+
+// The maximum number of distinct messages kept, to bound the memory used by
+// a badly corrupted status file.
+const MAX_DISTINCT_MESSAGES: usize = 32;
+
+/// A server scored with the default status instead of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultedServer {
+    // The position of the server in the logicals.
+    pub logical_index: usize,
+    // The status index referenced by the logical.
+    pub status_index: ServerIndex,
+    // Why the status of the server could not be used.
+    pub reason: String,
+}
+
+/// Tallies the server status parsing errors by message.
+///
+/// Use `ParseErrorCollector::sink` as the `log_errors` callback of
+/// `Parser::get`. Only the first 32 distinct messages are tallied, the
+/// errors with other messages are only counted in `total`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseErrorCollector {
+    counts: BTreeMap<String, usize>,
+    total: usize,
+    defaulted: Vec<DefaultedServer>,
+}
+
+impl ParseErrorCollector {
+    /// Returns an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a parsing error of the server at the given index.
+    pub fn record(&mut self, _index: usize, _byte_offset: usize, error: &str) {
+        self.total += 1;
+
+        if let Some(count) = self.counts.get_mut(error) {
+            *count += 1;
+        } else if self.counts.len() < MAX_DISTINCT_MESSAGES {
+            self.counts.insert(error.to_owned(), 1);
+        }
+    }
+
+    /// Records a server scored with the default status.
+    pub fn record_defaulted(&mut self, server: DefaultedServer) {
+        self.defaulted.push(server);
+    }
+
+    /// Returns the servers scored with the default status, in the order
+    /// they were recorded.
+    pub fn defaulted(&self) -> &[DefaultedServer] {
+        &self.defaulted
+    }
+
+    /// Returns a callback recording the errors into this collector.
+    pub fn sink(&mut self) -> impl FnMut(usize, usize, &str) + '_ {
+        |index, byte_offset, error| self.record(index, byte_offset, error)
+    }
+
+    /// Returns the number of errors recorded for each distinct message, in
+    /// message order.
+    pub fn counts(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.counts
+            .iter()
+            .map(|(message, &count)| (message.as_str(), count))
+    }
+
+    /// Returns the total number of errors recorded.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns a bool indicating whether no error was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_parse_error_collector() {
+        let mut collector = ParseErrorCollector::new();
+        assert!(collector.is_empty());
+
+        {
+            let mut sink = collector.sink();
+            sink(0, 0, "bad load");
+            sink(3, 18, "bad partial score");
+            sink(5, 30, "bad load");
+            for i in 0..MAX_DISTINCT_MESSAGES {
+                sink(i, 0, &format!("error {i}"));
+            }
+        }
+
+        assert_eq!(collector.total(), 3 + MAX_DISTINCT_MESSAGES);
+        assert_eq!(collector.counts().count(), MAX_DISTINCT_MESSAGES);
+        assert!(collector.counts().any(|count| count == ("bad load", 2)));
+        assert!(collector
+            .counts()
+            .any(|count| count == ("bad partial score", 1)));
+    }
+}
@@ -2,13 +2,18 @@
 // Copyright (c) 2025 Proton AG
 // -----------------------------------------------------------------------------
 use super::server_status::ServerStatus;
+use super::version::Version;
+use crate::logical::Logical;
 use crate::{error::*, status::server_status::ServerStatusResult};
 
 pub const VERSION_HEADER: [u8; 4] = [1_u8, 0_u8, 0_u8, 0_u8];
+pub const VERSION_HEADER_V2: [u8; 4] = [2_u8, 0_u8, 0_u8, 0_u8];
 const SERVER_SIZE: usize = 6; // 1 byte status, 1 byte load, 4 bytes partial score
+const SERVER_SIZE_V2: usize = SERVER_SIZE + 6; // v1 record, 4 bytes penalty, 2 bytes load_bp
 
 fn handle_errors(
     index: usize,
+    byte_offset: usize,
     result: ServerStatusResult<ServerStatus>,
     default: &ServerStatus,
     log_errors: &mut impl FnMut(usize, usize, &str),
@@ -16,14 +21,37 @@ fn handle_errors(
     match result {
         Ok(server_status) => server_status,
         Err(error) => {
-            log_errors(index, index * SERVER_SIZE, &error.0);
+            log_errors(index, byte_offset, &error.0);
             default.clone()
         }
     }
 }
+
+// Returns the size of a server record for the given version, or None if the
+// version is not supported.
+fn record_size(version: Version) -> Option<usize> {
+    const V1: Version = Version::from_le_bytes(VERSION_HEADER);
+    const V2: Version = Version::from_le_bytes(VERSION_HEADER_V2);
+
+    match version {
+        V1 => Some(SERVER_SIZE),
+        V2 => Some(SERVER_SIZE_V2),
+        _ => None,
+    }
+}
+
 /// Interpretes a byte stream as a status file containing multiple servers.
+///
+/// Version 1 status files contain 6 byte records. Version 2 status files
+/// contain 12 byte records, made of a version 1 record followed by the
+/// penalty of the server as a 32-bit little endian float and the load in
+/// basis points as a 16-bit little endian integer.
 #[derive(Debug)]
-pub struct Parser<'a>(&'a [u8]);
+pub struct Parser<'a> {
+    version: Version,
+    records: &'a [u8],
+    record_size: usize,
+}
 
 impl Parser<'_> {
     // Returns the server at the given index.
@@ -39,25 +67,83 @@ impl Parser<'_> {
         default: &ServerStatus,
         log_errors: &mut impl FnMut(usize, usize, &str),
     ) -> ServerStatus {
-        if i >= self.len() {
+        let Some(record) = self.raw_record(i) else {
             return default.clone();
-        }
-
-        let lower = i * SERVER_SIZE;
-        let upper = lower + SERVER_SIZE;
+        };
 
+        // `raw_record` checked that `i` is in bounds, so the offset of the
+        // record can't overflow.
         handle_errors(
             i,
-            ServerStatus::try_from(&self.0[lower..upper]),
+            i * self.record_size,
+            ServerStatus::try_from(record),
             default,
             log_errors,
         )
     }
 
+    /// Returns the status of the given logical, as `get` does for the index
+    /// of its status reference.
+    ///
+    pub fn get_for(
+        &self,
+        logical: &Logical,
+        default: &ServerStatus,
+        log_errors: &mut impl FnMut(usize, usize, &str),
+    ) -> ServerStatus {
+        // An index that doesn't fit in a usize is out of bounds anyway.
+        match logical.status_reference.index.to_usize() {
+            Some(i) => self.get(i, default, log_errors),
+            None => default.clone(),
+        }
+    }
+
+    /// Returns the raw bytes of the server record at the given index, or
+    /// `None` if the index is out of bounds.
+    ///
+    /// The record is 6 bytes long for version 1 status files and 12 bytes
+    /// long for version 2 status files. The bytes are not validated, which
+    /// makes this useful for inspecting records that `get` would replace
+    /// with the default status.
+    ///
+    pub fn raw_record(&self, i: usize) -> Option<&[u8]> {
+        // The bounds check must come first, `i * self.record_size` overflows
+        // for indices close to `usize::MAX`.
+        if i >= self.len() {
+            return None;
+        }
+
+        let lower = i * self.record_size;
+        let upper = lower + self.record_size;
+
+        Some(&self.records[lower..upper])
+    }
+
+    /// Returns the version of the status file.
+    ///
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
     /// Returns the number of servers in the status file.
     ///
     pub fn len(&self) -> usize {
-        self.0.len() / SERVER_SIZE
+        self.records.len() / self.record_size
+    }
+
+    /// Returns the number of bytes after the version header that do not
+    /// form a complete server record.
+    ///
+    /// `Parser::try_from` rejects status files for which this is not 0, this
+    /// helps diagnose by how many bytes such a file is off.
+    ///
+    pub fn trailing_bytes(bytes: &[u8]) -> usize {
+        let record_size = bytes
+            .first_chunk::<4>()
+            .and_then(|version| record_size(Version::from_le_bytes(*version)))
+            .unwrap_or(SERVER_SIZE);
+
+        bytes.len().saturating_sub(VERSION_HEADER.len()) % record_size
     }
 
     /// Returns a bool indicating whether the status file contains
@@ -67,18 +153,101 @@ impl Parser<'_> {
         self.len() == 0
     }
 
+    /// Returns true when every record of the status file is made of zero
+    /// bytes, i.e. decodes to `ServerStatus::default()` (with a zero penalty
+    /// and load in basis points for version 2 status files).
+    ///
+    /// Such a file marks every server as disabled with a load of 0, which
+    /// usually comes from a placeholder file or a back end error rather than
+    /// from every server being down. Callers can use it to keep the previous
+    /// scores instead. A status file without servers is all default too.
+    ///
+    pub fn is_all_default(&self) -> bool {
+        self.records.iter().all(|&byte| byte == 0)
+    }
+
     /// Returns an iterator over the servers in the status file.
     ///
     pub fn iter(&self) -> impl Iterator<Item = ServerStatus> + '_ {
         // Waiting on Iterator::array_chunks to stabilize.
         // https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.array_chunks
         // before we can remove this expect call
-        self.0.chunks_exact(SERVER_SIZE).map(|chunk| {
-            let array: &[u8; 6] =
-                chunk.try_into().expect("Chunk size is not correct");
-            ServerStatus::from(array)
+        self.records.chunks_exact(self.record_size).map(|chunk| {
+            ServerStatus::decode(chunk).expect("Chunk size is not correct")
         })
     }
+
+    /// Returns an iterator over the status byte of each server in the status
+    /// file, i.e. its `ServerStatus::status` flags.
+    ///
+    /// Unlike `iter`, the rest of the records is not decoded, which makes
+    /// scans that only look at the flags (e.g. counting the enabled servers)
+    /// cheaper.
+    ///
+    pub fn iter_status_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.records
+            .chunks_exact(self.record_size)
+            .map(|chunk| chunk[0])
+    }
+
+    /// Returns all the servers in the status file, as returned by `iter`.
+    ///
+    /// The records are decoded without being validated, so invalid records
+    /// are included as is instead of being replaced with a default status.
+    ///
+    pub fn to_vec(&self) -> Vec<ServerStatus> {
+        self.iter().collect()
+    }
+
+    /// Returns all the servers in the status file, validated as by `get`.
+    ///
+    /// Invalid records are returned as a `Error::ParserError` describing
+    /// why they are invalid.
+    ///
+    pub fn to_vec_validated(&self) -> Vec<Result<ServerStatus>> {
+        self.records
+            .chunks_exact(self.record_size)
+            .map(|chunk| {
+                ServerStatus::try_from(chunk)
+                    .map_err(|error| Error::ParserError(error.0))
+            })
+            .collect()
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses a status file preceded by its number of servers, as a 32-bit
+    /// little endian integer.
+    ///
+    /// Exactly that many servers are parsed and any bytes after them are
+    /// ignored, which allows parsing status files embedded in a larger
+    /// buffer.
+    ///
+    pub fn try_from_prefixed(bytes: &'a [u8]) -> Result<Self> {
+        let Some((count, status_file)) = bytes.split_first_chunk::<4>() else {
+            return Err(Error::ParserError(
+                "Failed to read the number of servers".to_string(),
+            ));
+        };
+        let count = u32::from_le_bytes(*count) as usize;
+
+        let record_size = status_file
+            .first_chunk::<4>()
+            .and_then(|version| record_size(Version::from_le_bytes(*version)))
+            .unwrap_or(SERVER_SIZE);
+
+        let len = count
+            .checked_mul(record_size)
+            .and_then(|len| len.checked_add(VERSION_HEADER.len()))
+            .filter(|&len| len <= status_file.len())
+            .ok_or_else(|| {
+                Error::ParserError(format!(
+                    "Status file is truncated, expected {count} servers"
+                ))
+            })?;
+
+        Self::try_from(&status_file[..len])
+    }
 }
 
 impl<'a> std::convert::TryFrom<&'a [u8]> for Parser<'a> {
@@ -91,23 +260,42 @@ impl<'a> std::convert::TryFrom<&'a [u8]> for Parser<'a> {
             ));
         }
 
-        let version: &[u8; 4] = value[0..4].try_into().map_err(|_| {
+        let version: [u8; 4] = value[0..4].try_into().map_err(|_| {
             Error::ParserError(
                 "Failed to convert first 4 bytes in magic number".to_string(),
             )
         })?;
+        let version = Version::from_le_bytes(version);
 
-        if version != &VERSION_HEADER {
+        let Some(record_size) = record_size(version) else {
             return Err(Error::ParserError("Invalid magic number".to_string()));
-        }
+        };
+
+        let records_len = value.len() - 4;
+        if !records_len.is_multiple_of(record_size) {
+            // Records laid out for another version (e.g. a v2 header on v1
+            // records) are reported as such, to ease migrations.
+            if [SERVER_SIZE, SERVER_SIZE_V2]
+                .into_iter()
+                .any(|size| records_len.is_multiple_of(size))
+            {
+                return Err(Error::RecordSizeMismatch {
+                    version: version.0,
+                    expected_record_size: record_size as u64,
+                    remainder: (records_len % record_size) as u64,
+                });
+            }
 
-        if ((value.len() - 4) % SERVER_SIZE) != 0 {
             return Err(Error::ParserError(
                 "Status file is corrupt".to_string(),
             ));
         }
 
-        Ok(Self(&value[4..]))
+        Ok(Self {
+            version,
+            records: &value[4..],
+            record_size,
+        })
     }
 }
 
@@ -160,16 +348,22 @@ mod tests {
                 status: 1,
                 load: 57,
                 partial_score: 0.97,
+                status_penalty: None,
+                load_bp: None,
             }),
             make_server(ServerStatus {
                 status: 1,
                 load: 75,
                 partial_score: 0.99,
+                status_penalty: None,
+                load_bp: None,
             }),
             make_server(ServerStatus {
                 status: 1,
                 load: 23,
                 partial_score: 0.43,
+                status_penalty: None,
+                load_bp: None,
             }),
         ]);
 
@@ -182,10 +376,15 @@ mod tests {
 
         let default = ServerStatus::default();
 
+        assert_eq!(status.version(), Version(1));
         assert_eq!(status.len(), 3);
         assert_eq!(status.get(0, &default, &mut errors).status, 1);
         assert_eq!(status.get(0, &default, &mut errors).load, 57);
         assert_eq!(status.get(0, &default, &mut errors).partial_score, 0.97);
+        assert_eq!(
+            status.get(0, &default, &mut errors).load_basis_points(),
+            5700
+        );
         assert_eq!(status.get(1, &default, &mut errors).status, 1);
         assert_eq!(status.get(1, &default, &mut errors).load, 75);
         assert_eq!(status.get(1, &default, &mut errors).partial_score, 0.99);
@@ -194,6 +393,84 @@ mod tests {
         assert_eq!(status.get(2, &default, &mut errors).partial_score, 0.43);
     }
 
+    #[test_log::test]
+    fn test_v2_status_file() {
+        let mut simple_status = VERSION_HEADER_V2.to_vec();
+        for (server, penalty, load_bp) in [
+            (
+                ServerStatus {
+                    status: 1,
+                    load: 57,
+                    partial_score: 0.97,
+                    status_penalty: None,
+                    load_bp: None,
+                },
+                2.5_f32,
+                5712_u16,
+            ),
+            (
+                ServerStatus {
+                    status: 3,
+                    load: 23,
+                    partial_score: 0.43,
+                    status_penalty: None,
+                    load_bp: None,
+                },
+                -1.0_f32, // Invalid penalty
+                2300_u16,
+            ),
+        ] {
+            simple_status.extend_from_slice(&make_server(server));
+            simple_status.extend_from_slice(&penalty.to_le_bytes());
+            simple_status.extend_from_slice(&load_bp.to_le_bytes());
+        }
+
+        let status = Parser::try_from(&simple_status[..])
+            .expect("Failed to parse status file");
+
+        let mut error_reported = 0;
+        let mut handle_errors = |_index: usize, offset: usize, _error: &str| {
+            assert_eq!(offset, SERVER_SIZE_V2);
+            error_reported += 1;
+        };
+
+        let default = ServerStatus::default();
+        assert!(status.version() >= Version(2));
+        assert_eq!(status.len(), 2);
+        assert_eq!(
+            status.get(0, &default, &mut handle_errors),
+            ServerStatus {
+                status: 1,
+                load: 57,
+                partial_score: 0.97,
+                status_penalty: Some(2.5),
+                load_bp: Some(5712),
+            }
+        );
+        assert_eq!(
+            status
+                .get(0, &default, &mut handle_errors)
+                .load_basis_points(),
+            5712
+        );
+        assert_eq!(status.get(1, &default, &mut handle_errors), default);
+        assert_eq!(error_reported, 1);
+        assert_eq!(status.raw_record(1).map(<[u8]>::len), Some(SERVER_SIZE_V2));
+
+        // A v2 file with v1 sized records is corrupt
+        let mut v1_layout = VERSION_HEADER_V2.to_vec();
+        v1_layout.extend_from_slice(&[0_u8; SERVER_SIZE * 3]);
+        assert!(matches!(
+            Parser::try_from(&v1_layout[..]),
+            Err(Error::RecordSizeMismatch {
+                version: 2,
+                expected_record_size: 12,
+                remainder: 6,
+            })
+        ));
+        assert_eq!(Parser::trailing_bytes(&v1_layout), 6);
+    }
+
     #[test_log::test]
     fn test_simple_empty() {
         let simple_status = [8_u8];
@@ -234,12 +511,64 @@ mod tests {
         };
     }
 
+    #[test_log::test]
+    fn test_trailing_bytes() {
+        let mut simple_status =
+            make_status_file(&[make_server(ServerStatus {
+                status: 1,
+                load: 57,
+                partial_score: 0.97,
+                status_penalty: None,
+                load_bp: None,
+            })]);
+        assert_eq!(Parser::trailing_bytes(&simple_status), 0);
+
+        simple_status.extend_from_slice(&[0_u8, 0_u8]);
+        assert_eq!(Parser::trailing_bytes(&simple_status), 2);
+
+        assert_eq!(Parser::trailing_bytes(&[]), 0);
+        assert_eq!(Parser::trailing_bytes(&[1_u8, 0_u8, 0_u8, 0_u8, 0_u8]), 1);
+    }
+
+    #[test_log::test]
+    fn test_try_from_prefixed() {
+        let server = make_server(ServerStatus {
+            status: 1,
+            load: 57,
+            partial_score: 0.97,
+            status_penalty: None,
+            load_bp: None,
+        });
+        let mut bytes = 2_u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&make_status_file(&[server, server]));
+        bytes.extend_from_slice(&[0xff_u8; 5]); // Framing bytes
+
+        let status = Parser::try_from_prefixed(&bytes)
+            .expect("Failed to parse prefixed status file");
+        assert_eq!(status.len(), 2);
+        assert!(status.iter().all(|server| server.load == 57));
+
+        // Fewer servers than announced
+        bytes[0] = 3;
+        assert!(Parser::try_from_prefixed(&bytes).is_err());
+
+        // No room for the count
+        assert!(Parser::try_from_prefixed(&bytes[..3]).is_err());
+
+        // An invalid version is still rejected
+        let mut bytes = 0_u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[9_u8, 0_u8, 0_u8, 0_u8]);
+        assert!(Parser::try_from_prefixed(&bytes).is_err());
+    }
+
     #[test_log::test]
     fn test_out_of_range_index() {
         let server_zero = ServerStatus {
             status: 1,
             load: 57,
             partial_score: 0.97,
+            status_penalty: None,
+            load_bp: None,
         };
         let simple_status =
             make_status_file(&[make_server(server_zero.clone())]);
@@ -259,10 +588,141 @@ mod tests {
             status.get(1, &default, &mut handle_errors),
             ServerStatus::default()
         );
+        assert_eq!(
+            status.get(usize::MAX, &default, &mut handle_errors),
+            ServerStatus::default()
+        );
+        assert_eq!(status.raw_record(usize::MAX), None);
         // Its not an error to request an out-of-range index
         assert!(!error_reported);
     }
 
+    #[test_log::test]
+    fn test_iter_status_bytes() {
+        let server = |status| {
+            make_server(ServerStatus {
+                status,
+                load: 42,
+                partial_score: 0.5,
+                ..Default::default()
+            })
+        };
+        let simple_status =
+            make_status_file(&[server(3), server(0), server(7)]);
+
+        let status = Parser::try_from(&simple_status[..])
+            .expect("Failed to parse status file");
+        assert_eq!(status.iter_status_bytes().collect::<Vec<_>>(), [3, 0, 7]);
+        assert!(status
+            .iter_status_bytes()
+            .eq(status.iter().map(|server| server.status)));
+
+        let mut status_file = VERSION_HEADER_V2.to_vec();
+        for status in [5_u8, 1_u8] {
+            status_file.extend_from_slice(&server(status));
+            status_file.extend_from_slice(&[0_u8; 6]); // Penalty and load_bp
+        }
+        let status = Parser::try_from(&status_file[..])
+            .expect("Failed to parse status file");
+        assert_eq!(status.iter_status_bytes().collect::<Vec<_>>(), [5, 1]);
+    }
+
+    #[test_log::test]
+    fn test_is_all_default() {
+        let zero = make_server(ServerStatus::default());
+        let enabled = make_server(ServerStatus {
+            status: 1,
+            ..Default::default()
+        });
+
+        let is_all_default = |servers: &[[u8; 6]]| {
+            let status_file = make_status_file(servers);
+            Parser::try_from(&status_file[..])
+                .expect("Failed to parse status file")
+                .is_all_default()
+        };
+
+        assert!(is_all_default(&[zero, zero, zero]));
+        assert!(is_all_default(&[]));
+        assert!(!is_all_default(&[zero, enabled, zero]));
+
+        let mut status_file = VERSION_HEADER_V2.to_vec();
+        status_file.extend_from_slice(&[0_u8; 24]);
+        let status = Parser::try_from(&status_file[..])
+            .expect("Failed to parse status file");
+        assert!(status.is_all_default());
+
+        status_file[4 + 6] = 1; // Penalty of server 0
+        let status = Parser::try_from(&status_file[..])
+            .expect("Failed to parse status file");
+        assert!(!status.is_all_default());
+    }
+
+    #[test_log::test]
+    fn test_get_for() {
+        let server_one = ServerStatus {
+            status: 3,
+            load: 23,
+            partial_score: 0.43,
+            status_penalty: None,
+            load_bp: None,
+        };
+        let simple_status = make_status_file(&[
+            make_server(ServerStatus::default()),
+            make_server(server_one.clone()),
+        ]);
+        let status = Parser::try_from(&simple_status[..])
+            .expect("Failed to parse status file");
+
+        let logical = |index| Logical {
+            status_reference: crate::StatusReference {
+                index: crate::ServerIndex(index),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let default = ServerStatus {
+            load: 99,
+            ..Default::default()
+        };
+
+        let mut handle_errors = |_: usize, _: usize, _: &str| {};
+        assert_eq!(
+            status.get_for(&logical(1), &default, &mut handle_errors),
+            server_one
+        );
+        assert_eq!(
+            status.get_for(&logical(u32::MAX), &default, &mut handle_errors),
+            default
+        );
+    }
+
+    #[test_log::test]
+    fn test_raw_record() {
+        let server_zero = make_server(ServerStatus {
+            status: 1,
+            load: 150, // Invalid load, but raw records are not validated
+            partial_score: 0.97,
+            status_penalty: None,
+            load_bp: None,
+        });
+        let server_one = make_server(ServerStatus {
+            status: 3,
+            load: 23,
+            partial_score: 0.43,
+            status_penalty: None,
+            load_bp: None,
+        });
+        let simple_status = make_status_file(&[server_zero, server_one]);
+
+        let status = Parser::try_from(&simple_status[..])
+            .expect("Failed to parse status file");
+
+        assert_eq!(status.raw_record(0), Some(&server_zero[..]));
+        assert_eq!(status.raw_record(1), Some(&server_one[..]));
+        assert_eq!(status.raw_record(2), None);
+    }
+
     #[test_log::test]
     fn test_invalid_load() {
         let simple_status = make_status_file(&[
@@ -270,11 +730,15 @@ mod tests {
                 status: 1,
                 load: 150, // Invalid load
                 partial_score: 1.0,
+                status_penalty: None,
+                load_bp: None,
             }),
             make_server(ServerStatus {
                 status: 1,
                 load: 200, // Invalid load
                 partial_score: 1.0,
+                status_penalty: None,
+                load_bp: None,
             }),
         ]);
 
@@ -293,6 +757,35 @@ mod tests {
         // This error should be reported twice, once for each invalid server
         assert_eq!(error_reported, 2);
     }
+
+    #[test_log::test]
+    fn test_to_vec() {
+        let valid = ServerStatus {
+            status: 1,
+            load: 57,
+            partial_score: 0.97,
+            status_penalty: None,
+            load_bp: None,
+        };
+        let invalid = ServerStatus {
+            load: 150, // Invalid load
+            ..valid.clone()
+        };
+        let simple_status = make_status_file(&[
+            make_server(valid.clone()),
+            make_server(invalid.clone()),
+        ]);
+
+        let status = Parser::try_from(&simple_status[..])
+            .expect("Failed to parse status file");
+
+        assert_eq!(status.to_vec(), [valid.clone(), invalid]);
+
+        let validated = status.to_vec_validated();
+        assert_eq!(validated.len(), 2);
+        assert_eq!(validated[0].as_ref().ok(), Some(&valid));
+        assert!(matches!(validated[1], Err(Error::ParserError(_))));
+    }
     #[test_log::test]
     fn test_invalid_partial_score() {
         let simple_status = make_status_file(&[
@@ -300,11 +793,15 @@ mod tests {
                 status: 1,
                 load: 0,
                 partial_score: 100.0, // Invalid partial score
+                status_penalty: None,
+                load_bp: None,
             }),
             make_server(ServerStatus {
                 status: 1,
                 load: 0,
                 partial_score: -100.0, // Invalid partial score
+                status_penalty: None,
+                load_bp: None,
             }),
         ]);
 
@@ -6,21 +6,45 @@
 pub struct ServerStatusError(pub(crate) String);
 pub type ServerStatusResult<T> = std::result::Result<T, ServerStatusError>;
 
+/// The maximum load of a valid server status, in percent.
+pub const MAX_LOAD: u8 = 100;
+
+/// The range of the partial score of a valid server status.
+pub const PARTIAL_SCORE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.0;
+
 pub fn validate_server(
     server: ServerStatus,
 ) -> ServerStatusResult<ServerStatus> {
-    if server.load > 100 {
-        return Err(ServerStatusError(
-            "Server load must be between 0 and 100".into(),
-        ));
+    if server.load > MAX_LOAD {
+        return Err(ServerStatusError(format!(
+            "Server load must be between 0 and {MAX_LOAD}"
+        )));
     }
 
-    if server.partial_score < 0.0 || server.partial_score > 1.0 {
+    if server.partial_score < *PARTIAL_SCORE_RANGE.start()
+        || server.partial_score > *PARTIAL_SCORE_RANGE.end()
+    {
+        return Err(ServerStatusError(format!(
+            "Server partial score must be between {:.1} and {:.1}",
+            PARTIAL_SCORE_RANGE.start(),
+            PARTIAL_SCORE_RANGE.end()
+        )));
+    }
+
+    if server.load_bp.is_some_and(|load_bp| load_bp > 10_000) {
         return Err(ServerStatusError(
-            "Server partial score must be between 0.0 and 1.0".into(),
+            "Server load must be between 0 and 10000 basis points".into(),
         ));
     }
 
+    if let Some(status_penalty) = server.status_penalty {
+        if !status_penalty.is_finite() || status_penalty < 0.0 {
+            return Err(ServerStatusError(
+                "Server penalty must be a positive finite number".into(),
+            ));
+        }
+    }
+
     Ok(server)
 }
 
@@ -29,10 +53,16 @@ pub fn validate_server(
 pub struct ServerStatus {
     pub status: u8,
     pub load: u8,
-    pub partial_score: f32, // This is a 32-bit float to match the binary format
-                            // of binary status files.
-                            // We keep it as a 32-bit float to keep the size
-                            // small.
+    // This is a 32-bit float to match the binary format of binary status
+    // files. We keep it as a 32-bit float to keep the size small.
+    pub partial_score: f32,
+    // The penalty computed by the back end at the time the status file was
+    // generated. Only present in v2 status files.
+    pub status_penalty: Option<f32>,
+    // The load in basis points (0 to 10000), which avoids the 1% quantization
+    // of `load`. Only present in v2 status files, in which case `load` is
+    // this value rounded to a percentage.
+    pub load_bp: Option<u16>,
 }
 
 impl From<&[u8; 6]> for ServerStatus {
@@ -41,18 +71,55 @@ impl From<&[u8; 6]> for ServerStatus {
             status: src[0],
             load: src[1],
             partial_score: f32::from_le_bytes([src[2], src[3], src[4], src[5]]),
+            status_penalty: None,
+            load_bp: None,
         }
     }
 }
 
-impl TryFrom<&[u8]> for ServerStatus {
-    type Error = ServerStatusError;
-    fn try_from(src: &[u8]) -> ServerStatusResult<Self> {
+impl From<&[u8; 12]> for ServerStatus {
+    fn from(src: &[u8; 12]) -> Self {
+        let load_bp = u16::from_le_bytes([src[10], src[11]]);
+
+        Self {
+            status: src[0],
+            // Rounded to the nearest percentage, an invalid load_bp saturates
+            // and is rejected by the validation.
+            load: u8::try_from((load_bp as u32 + 50) / 100).unwrap_or(u8::MAX),
+            partial_score: f32::from_le_bytes([src[2], src[3], src[4], src[5]]),
+            status_penalty: Some(f32::from_le_bytes([
+                src[6], src[7], src[8], src[9],
+            ])),
+            load_bp: Some(load_bp),
+        }
+    }
+}
+
+impl ServerStatus {
+    /// Returns the load in basis points (0 to 10000). For v1 status files it
+    /// is derived from the load percentage.
+    pub fn load_basis_points(&self) -> u16 {
+        self.load_bp.unwrap_or(self.load as u16 * 100)
+    }
+
+    // Decodes a v1 (6 bytes) or v2 (12 bytes) record without validating it.
+    pub(crate) fn decode(src: &[u8]) -> ServerStatusResult<Self> {
+        if let Ok(bytes) = <&[u8; 12]>::try_from(src) {
+            return Ok(Self::from(bytes));
+        }
+
         let bytes: &[u8; 6] = src.try_into().map_err(|err| {
             ServerStatusError(format!("Not enough bytes to parse ServerStatus: {}, original size is {}", err, src.len()))
         })?;
 
-        validate_server(Self::from(bytes))
+        Ok(Self::from(bytes))
+    }
+}
+
+impl TryFrom<&[u8]> for ServerStatus {
+    type Error = ServerStatusError;
+    fn try_from(src: &[u8]) -> ServerStatusResult<Self> {
+        validate_server(Self::decode(src)?)
     }
 }
 
@@ -62,6 +129,8 @@ impl Default for ServerStatus {
             status: 0,
             load: 0,
             partial_score: 0.0,
+            status_penalty: None,
+            load_bp: None,
         }
     }
 }
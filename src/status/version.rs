@@ -0,0 +1,37 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+
+/// Version of a status file, stored as a little endian dword at the start of
+/// the file.
+///
+/// Versions are ordered, so layout changes can be gated with comparisons
+/// such as `version >= Version(2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version(pub u32);
+
+impl Version {
+    /// Reads a version from its little endian representation.
+    pub const fn from_le_bytes(bytes: [u8; 4]) -> Self {
+        Self(u32::from_le_bytes(bytes))
+    }
+
+    /// Returns the little endian representation of the version.
+    pub const fn to_le_bytes(self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_version() {
+        assert_eq!(Version::from_le_bytes([1, 0, 0, 0]), Version(1));
+        assert_eq!(Version::from_le_bytes([0, 1, 0, 0]), Version(256));
+        assert_eq!(Version(2).to_le_bytes(), [2, 0, 0, 0]);
+        assert!(Version(2) >= Version(1));
+        assert!(Version(256) > Version(2));
+    }
+}
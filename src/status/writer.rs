@@ -0,0 +1,209 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use super::parser::{Parser, VERSION_HEADER};
+use super::server_status::{validate_server, ServerStatus};
+use crate::compute_score::{
+    STATUS_AUTOCONNECTABLE, STATUS_ENABLED, STATUS_VISIBLE,
+};
+use crate::error::*;
+use crate::load::Load;
+
+const STATUS_FLAGS: u8 =
+    STATUS_ENABLED | STATUS_VISIBLE | STATUS_AUTOCONNECTABLE;
+
+/// Builds a version 1 status file from scored loads, e.g. to generate test
+/// fixtures from observed outputs.
+///
+/// The status byte and the load of each record come from its `Load`, the
+/// flags taking precedence over the raw status byte. The partial scores are
+/// not kept in the loads, so `partial_scores[i]` is the partial score of
+/// record `i`, it must have the same length as `loads`.
+///
+/// Returns `Error::ParserError` if a record would be invalid, as the parser
+/// would then replace it with the default status.
+pub fn status_file_from_loads(
+    loads: &[Load],
+    partial_scores: &[f32],
+) -> Result<Vec<u8>> {
+    if partial_scores.len() != loads.len() {
+        return Err(Error::PartialScoresLengthNotConsistent {
+            loads: loads.len() as u64,
+            partial_scores: partial_scores.len() as u64,
+        });
+    }
+
+    let mut status_file = VERSION_HEADER.to_vec();
+    for (i, (load, &partial_score)) in
+        std::iter::zip(loads, partial_scores).enumerate()
+    {
+        let flags = [
+            (load.is_enabled, STATUS_ENABLED),
+            (load.is_visible, STATUS_VISIBLE),
+            (load.is_autoconnectable, STATUS_AUTOCONNECTABLE),
+        ]
+        .into_iter()
+        .filter(|(is_set, _)| *is_set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+
+        let status = validate_server(ServerStatus {
+            status: (load.status & !STATUS_FLAGS) | flags,
+            load: load.load,
+            partial_score,
+            ..Default::default()
+        })
+        .map_err(|error| {
+            Error::ParserError(format!("Server {i}: {}", error.0))
+        })?;
+
+        status_file.push(status.status);
+        status_file.push(status.load);
+        status_file.extend_from_slice(&status.partial_score.to_le_bytes());
+    }
+
+    Ok(status_file)
+}
+
+/// Merges two status files of the same version and number of servers, e.g.
+/// received from two regions during a back end failover.
+///
+/// Each record of the merged file is the record of whichever file marks the
+/// server enabled. When both or neither do, the record with the higher load
+/// is kept, as the fresher one, and ties keep the record of `a`. Invalid
+/// records count as disabled with a load of 0, and are copied as is when
+/// they are kept.
+///
+/// Returns `Error::RecordCountsNotConsistent` if the files don't have the
+/// same number of servers, and `Error::ParserError` if either can't be
+/// parsed or their versions differ.
+pub fn merge_status_prefer_enabled(a: &[u8], b: &[u8]) -> Result<Vec<u8>> {
+    let a = Parser::try_from(a)?;
+    let b = Parser::try_from(b)?;
+
+    if a.version() != b.version() {
+        return Err(Error::ParserError(format!(
+            "Status file versions {} and {} can't be merged",
+            a.version().0,
+            b.version().0
+        )));
+    }
+    if a.len() != b.len() {
+        return Err(Error::RecordCountsNotConsistent {
+            first: a.len() as u64,
+            second: b.len() as u64,
+        });
+    }
+
+    let status_is_unknown = ServerStatus::default();
+    let freshness = |parser: &Parser, i| {
+        let status = parser.get(i, &status_is_unknown, &mut |_, _, _| {});
+        (status.status & STATUS_ENABLED != 0, status.load)
+    };
+
+    let mut status_file = a.version().to_le_bytes().to_vec();
+    for i in 0..a.len() {
+        let fresher = if freshness(&b, i) > freshness(&a, i) {
+            &b
+        } else {
+            &a
+        };
+        // `i` is in bounds of both files.
+        status_file.extend_from_slice(fresher.raw_record(i).unwrap_or(&[]));
+    }
+
+    Ok(status_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_loads;
+    use crate::test_fixtures::make_logicals;
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_status_file_from_loads() -> AnyResult {
+        let status_file = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            7_u8, 10_u8, 0_u8, 0_u8, 0x80_u8, 0x3e_u8, // Autoconnectable
+            2_u8, 20_u8, 0_u8, 0_u8, 0x00_u8, 0x3f_u8, // Disabled
+            0x83_u8, 30_u8, 0_u8, 0_u8, 0x40_u8, 0x3f_u8, // Unknown bit
+        ];
+        let logicals = make_logicals(3);
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads(&mut loads, &logicals, &status_file, &None, &None)?;
+
+        // Decoding then encoding gives back the same status file
+        assert_eq!(
+            status_file_from_loads(&loads, &[0.25, 0.5, 0.75])?,
+            status_file
+        );
+
+        // The flags take precedence over the raw status byte
+        loads[0].is_autoconnectable = false;
+        assert_eq!(
+            status_file_from_loads(&loads, &[0.25, 0.5, 0.75])?[4],
+            STATUS_ENABLED | STATUS_VISIBLE
+        );
+
+        assert!(matches!(
+            status_file_from_loads(&loads, &[0.25]),
+            Err(Error::PartialScoresLengthNotConsistent {
+                loads: 3,
+                partial_scores: 1
+            })
+        ));
+        assert!(matches!(
+            status_file_from_loads(&loads, &[0.25, 1.5, 0.75]),
+            Err(Error::ParserError(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_merge_status_prefer_enabled() -> AnyResult {
+        let a = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 10_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Enabled
+            2_u8, 20_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Disabled
+            3_u8, 30_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Higher load
+            3_u8, 40_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Same load
+            3_u8, 255_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Invalid
+        ];
+        let b = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            2_u8, 50_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Disabled
+            3_u8, 15_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Enabled
+            3_u8, 25_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Lower load
+            3_u8, 40_u8, 0_u8, 0_u8, 0x80_u8, 0x3f_u8, // Same load
+            3_u8, 5_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Valid
+        ];
+
+        let merged = merge_status_prefer_enabled(&a, &b)?;
+        assert_eq!(merged[..4], a[..4]);
+        assert_eq!(merged[4..10], a[4..10]);
+        assert_eq!(merged[10..16], b[10..16]);
+        assert_eq!(merged[16..22], a[16..22]);
+        assert_eq!(merged[22..28], a[22..28]);
+        assert_eq!(merged[28..34], b[28..34]);
+
+        assert!(matches!(
+            merge_status_prefer_enabled(&a, &b[..b.len() - 6]),
+            Err(Error::RecordCountsNotConsistent {
+                first: 5,
+                second: 4
+            })
+        ));
+        let mut v2 = vec![2_u8, 0_u8, 0_u8, 0_u8];
+        v2.extend_from_slice(&[0_u8; 12 * 5]);
+        assert!(matches!(
+            merge_status_prefer_enabled(&a, &v2),
+            Err(Error::ParserError(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+
+//! Logicals and status files shared by the unit tests.
+
+use crate::logical::{Logical, ServerIndex, StatusReference};
+
+/// Returns a logical referencing the status record `index`, every other
+/// field is defaulted.
+pub(crate) fn make_logical(index: u32) -> Logical {
+    Logical {
+        status_reference: StatusReference {
+            index: ServerIndex(index),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns `count` logicals referencing the first `count` status records, in
+/// order.
+pub(crate) fn make_logicals(count: u32) -> Vec<Logical> {
+    (0..count).map(make_logical).collect()
+}
+
+/// Returns a version 1 status file with a `(status, load, partial_score)`
+/// record per server. The records are not validated, so they can be used to
+/// test invalid ones.
+pub(crate) fn make_status_file(records: &[(u8, u8, f32)]) -> Vec<u8> {
+    let mut status_file = vec![1_u8, 0_u8, 0_u8, 0_u8]; // Version
+    for &(status, load, partial_score) in records {
+        status_file.extend_from_slice(&[status, load]);
+        status_file.extend_from_slice(&partial_score.to_le_bytes());
+    }
+
+    status_file
+}
@@ -1,7 +1,22 @@
 use super::*;
 use crate::compute_score::SCORE_NORMALIZATION_FACTOR;
 
-pub fn match_jitter(a: &v1::Server, b: &v1::Server) -> (f64, f64) {
+/// Returns the v1 score of `a` and the v2 score of `b` with the jitter of `a`
+/// applied, so that they can be compared.
+///
+/// Returns an error if either score or the jitter is not finite, as a NaN
+/// score would otherwise compare as matching every other score.
+pub fn match_jitter(a: &v1::Server, b: &v1::Server) -> Result<(f64, f64)> {
+    for (name, value) in [
+        ("v1 score", a.score),
+        ("v1 score jitter", a.score_jitter_bps),
+        ("v2 score", b.score),
+    ] {
+        if !value.is_finite() {
+            anyhow::bail!("Server {} has a non-finite {name}: {value}", a.name);
+        }
+    }
+
     let score_jitter_mbps = a.score_jitter_bps / 1_000_000.0;
     let a_score = a.score;
     let b_score = (b.score.fract()
@@ -9,10 +24,43 @@ pub fn match_jitter(a: &v1::Server, b: &v1::Server) -> (f64, f64) {
         .clamp(0.0, 1.0)
         + b.score.trunc();
 
-    (a_score, b_score)
+    Ok((a_score, b_score))
 }
 
+/// Returns the difference between the v1 score of `a` and the v2 score of
+/// `b`, after matching their jitter.
+///
+/// Returns `f64::INFINITY` when the scores can't be matched (see
+/// `match_jitter`), so that they exceed any acceptable variance.
 pub fn compute_variance(a: &v1::Server, b: &v1::Server) -> f64 {
-    let (a_score, b_score) = match_jitter(a, b);
-    (a_score - b_score).abs()
+    match match_jitter(a, b) {
+        Ok((a_score, b_score)) => (a_score - b_score).abs(),
+        Err(error) => {
+            log::error!("{error}");
+            f64::INFINITY
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_non_finite_scores() {
+        let server = |score| v1::Server {
+            name: "CH#1".to_string(),
+            score,
+            ..Default::default()
+        };
+
+        assert_eq!(compute_variance(&server(0.25), &server(0.5)), 0.25);
+
+        assert!(match_jitter(&server(0.25), &server(f64::NAN)).is_err());
+        assert!(match_jitter(&server(f64::NAN), &server(0.25)).is_err());
+        assert_eq!(
+            compute_variance(&server(0.25), &server(f64::NAN)),
+            f64::INFINITY
+        );
+    }
 }
@@ -1,4 +1,7 @@
-use super::Result;
+use super::{Fetch, Result};
+
+// The app version used to identify the test client to the backend.
+pub const DEFAULT_APP_VERSION: &str = "windows-vpn@4.1.0"; // TODO: replace with something generic
 
 #[derive(Debug, Clone)]
 pub enum Endpoints {
@@ -8,6 +11,21 @@ pub enum Endpoints {
 
 impl Endpoints {
     pub async fn new(default_cache_dir: &str) -> Result<Self> {
+        Self::new_with_env(
+            muon::env::EnvId::new_prod(),
+            DEFAULT_APP_VERSION,
+            default_cache_dir,
+        )
+        .await
+    }
+
+    /// Same as `new`, but logs into the given backend environment using the
+    /// given app version, so tests can target non-production backends.
+    pub async fn new_with_env(
+        env: muon::env::EnvId,
+        app: &str,
+        default_cache_dir: &str,
+    ) -> Result<Self> {
         let cache = std::path::PathBuf::from(
             std::env::var("PROTON_VPN_BINARY_STATUS_TEST_CACHE")
                 .unwrap_or(default_cache_dir.into()),
@@ -19,7 +37,8 @@ impl Endpoints {
                 std::env::var("PROTON_VPN_BINARY_STATUS_TEST_PASSWORD")?;
             let two_fa = std::env::var("PROTON_VPN_BINARY_STATUS_TEST_2FA")?;
             Ok(Self::Client((
-                Self::login(&user?, &password, &two_fa).await?,
+                Self::login_with_env(env, app, &user?, &password, &two_fa)
+                    .await?,
                 cache,
             )))
         } else {
@@ -42,11 +61,28 @@ impl Endpoints {
         user: &str,
         password: &str,
         two_fa: &str,
+    ) -> Result<muon::Client> {
+        Self::login_with_env(
+            muon::env::EnvId::new_prod(),
+            DEFAULT_APP_VERSION,
+            user,
+            password,
+            two_fa,
+        )
+        .await
+    }
+
+    pub async fn login_with_env(
+        env: muon::env::EnvId,
+        app: &str,
+        user: &str,
+        password: &str,
+        two_fa: &str,
     ) -> Result<muon::Client> {
         // First, define which app is using the client.
-        let app = muon::App::new("windows-vpn@4.1.0")?; // TODO: replace with something generic
+        let app = muon::App::new(app)?;
 
-        let store = Storage::default();
+        let store = Storage::new(env);
         let client = muon::Client::new(app, store)?;
         let auth = client.auth();
 
@@ -156,12 +192,28 @@ impl Endpoints {
     }
 }
 
+impl Fetch for Endpoints {
+    async fn get(
+        &mut self,
+        endpoint: &str,
+        header: Option<(&str, &str)>,
+    ) -> Result<Vec<u8>> {
+        Endpoints::get(self, endpoint, header).await
+    }
+}
+
 #[derive(Debug)]
 pub struct Storage(muon::env::EnvId, muon::client::Auth);
 
+impl Storage {
+    pub fn new(env: muon::env::EnvId) -> Self {
+        Self(env, Default::default())
+    }
+}
+
 impl Default for Storage {
     fn default() -> Self {
-        Self(muon::env::EnvId::new_prod(), Default::default())
+        Self::new(muon::env::EnvId::new_prod())
     }
 }
 
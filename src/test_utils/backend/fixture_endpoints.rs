@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use super::{Fetch, Result};
+
+/// Serves the backend requests from in-memory fixtures, without any network
+/// access or login.
+///
+/// Requests for endpoints without a fixture fail, the headers are ignored.
+#[derive(Debug, Default, Clone)]
+pub struct FixtureEndpoints {
+    responses: HashMap<String, Vec<u8>>,
+}
+
+impl FixtureEndpoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the fixtures with `body` served for `endpoint`, replacing the
+    /// previous body of that endpoint.
+    pub fn with(mut self, endpoint: &str, body: impl Into<Vec<u8>>) -> Self {
+        self.responses.insert(endpoint.to_owned(), body.into());
+        self
+    }
+}
+
+impl Fetch for FixtureEndpoints {
+    async fn get(
+        &mut self,
+        endpoint: &str,
+        _header: Option<(&str, &str)>,
+    ) -> Result<Vec<u8>> {
+        self.responses
+            .get(endpoint)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No fixture for {endpoint}"))
+    }
+}
@@ -3,7 +3,8 @@
 // -----------------------------------------------------------------------------
 
 //! This module provides test utilities for interacting with the https backend
-//! api.
+//! api. Only `Endpoints`, which makes the actual requests, needs the
+//! `test_utils_backend` feature, the rest works offline from fixtures.
 
 use anyhow::Result;
 
@@ -17,10 +18,40 @@ pub const USER_COUNTRY: &[u8; 2] = b"US";
 
 const NETZONE_HEADER: &str = "X-PM-netzone";
 
+/// The backend requests made by `v1::get_logicals` and `v2::get_logicals`,
+/// served either by the backend (`Endpoints`) or by in-memory fixtures
+/// (`FixtureEndpoints`).
+#[allow(async_fn_in_trait)]
+pub trait Fetch {
+    async fn get(
+        &mut self,
+        endpoint: &str,
+        header: Option<(&str, &str)>,
+    ) -> Result<Vec<u8>>;
+
+    async fn get_deserialized<T>(
+        &mut self,
+        endpoint: &str,
+        header: Option<(&str, &str)>,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let data = self.get(endpoint, header).await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+}
+
 pub mod compute_variance;
+#[cfg(feature = "test_utils_backend")]
 mod endpoints;
+mod fixture_endpoints;
+mod ranking;
 pub mod v1;
 pub mod v2;
 
 pub use compute_variance::compute_variance;
+#[cfg(feature = "test_utils_backend")]
 pub use endpoints::Endpoints;
+pub use fixture_endpoints::FixtureEndpoints;
+pub use ranking::assert_ranking_matches;
@@ -0,0 +1,68 @@
+use super::*;
+
+// Number of servers listed on each side of the first divergent rank.
+const CONTEXT_RANKS: usize = 2;
+
+/// Checks that the v1 and v2 servers are ranked in the same order.
+///
+/// Only the servers present in both lists with the same load are compared,
+/// after matching the jitter of the v2 scores to the v1 ones. Two different
+/// servers at the same rank are accepted when their scores differ by less
+/// than `tolerance`, so a tolerance of 0.0 requires the exact same order.
+///
+/// The error lists the first divergent rank and the servers around it in
+/// both rankings, or the first server whose score is not finite.
+pub fn assert_ranking_matches(
+    v1: &[v1::Server],
+    v2: &[v1::Server],
+    tolerance: f64,
+) -> Result<()> {
+    let lookup: std::collections::HashMap<&str, &v1::Server> =
+        v2.iter().map(|s| (s.name.as_str(), s)).collect();
+
+    let mut v1_s = Vec::new();
+    let mut v2_s = Vec::new();
+
+    for i in v1 {
+        if let Some(v2_server) = lookup.get(i.name.as_str()) {
+            if i.load == v2_server.load {
+                let (v1_score, v2_score) =
+                    compute_variance::match_jitter(i, v2_server)?;
+                v1_s.push((i.name.as_str(), v1_score));
+                v2_s.push((v2_server.name.as_str(), v2_score));
+            }
+        }
+    }
+
+    v1_s.sort_by(|a, b| a.1.total_cmp(&b.1));
+    v2_s.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let divergence = std::iter::zip(&v1_s, &v2_s)
+        .position(|(i, j)| i.0 != j.0 && (i.1 - j.1).abs() >= tolerance);
+
+    let Some(rank) = divergence else {
+        for (i, j) in std::iter::zip(&v1_s, &v2_s) {
+            log::info!("{:13} {:13} with   {:.8} {:.8}", i.0, j.0, i.1, j.1);
+        }
+
+        return Ok(());
+    };
+
+    let mut message =
+        format!("Ranking order mismatch at rank {rank} of {}:", v1_s.len());
+    let context = rank.saturating_sub(CONTEXT_RANKS)
+        ..(rank + CONTEXT_RANKS + 1).min(v1_s.len());
+    for k in context {
+        let (i, j) = (v1_s[k], v2_s[k]);
+        message.push_str(&format!(
+            "\n{} {k:6} v1 {:13} {:.8}   v2 {:13} {:.8}",
+            if k == rank { ">" } else { " " },
+            i.0,
+            i.1,
+            j.0,
+            j.1
+        ));
+    }
+
+    Err(anyhow::anyhow!(message))
+}
@@ -1,4 +1,4 @@
-use super::{Endpoints, Result, NETZONE_HEADER, USER_IP_ADDRESS};
+use super::{Fetch, Result, NETZONE_HEADER, USER_IP_ADDRESS};
 #[derive(
     serde::Serialize, serde::Deserialize, Debug, Default, Clone, PartialEq,
 )]
@@ -41,7 +41,7 @@ pub struct Logicals {
 }
 
 pub async fn get_logicals(
-    endpoints: &mut Endpoints,
+    endpoints: &mut impl Fetch,
     filter: impl Fn(&super::v1::Server) -> bool,
 ) -> Result<Logicals> {
     let mut logicals: Logicals = endpoints
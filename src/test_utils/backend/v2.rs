@@ -47,7 +47,7 @@ pub fn patch_secure_core(logicals: &mut super::v2::Logicals) {
 }
 
 pub async fn get_logicals(
-    endpoints: &mut Endpoints,
+    endpoints: &mut impl Fetch,
     filter: impl Fn(&super::v2::Server) -> bool,
 ) -> Result<super::v1::Logicals> {
     let mut logicals: Logicals =
@@ -92,7 +92,7 @@ pub async fn get_logicals(
             name: server.name.clone(),
             status: if load.is_enabled { 1 } else { 0 },
             load: load.load,
-            score: load.score,
+            score: load.score.value(),
             score_jitter_bps: 0.0,
             #[cfg(feature = "debug")]
             debug: load.debug.clone(),
@@ -105,3 +105,61 @@ pub async fn get_logicals(
 
     Ok(logicals_v1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test(tokio::test)]
+    async fn test_get_logicals_from_fixtures() -> Result<()> {
+        let logicals = r#"{
+            "StatusID": "fixture",
+            "LogicalServers": [
+                {
+                    "Name": "FR#1",
+                    "StatusReference": { "Index": 1, "Penalty": 0.0, "Cost": 0 },
+                    "EntryLocation": { "Latitude": 48.8566, "Longitude": 2.3522 },
+                    "ExitLocation": { "Latitude": 48.8566, "Longitude": 2.3522 },
+                    "ExitCountry": "FR"
+                },
+                {
+                    "Name": "CH#1",
+                    "StatusReference": { "Index": 0, "Penalty": 0.0, "Cost": 0 },
+                    "EntryLocation": { "Latitude": 46.2044, "Longitude": 6.1432 },
+                    "ExitLocation": { "Latitude": 46.2044, "Longitude": 6.1432 },
+                    "ExitCountry": "CH"
+                }
+            ]
+        }"#;
+        let status = [
+            1_u8, 0_u8, 0_u8, 0_u8, // Version
+            3_u8, 10_u8, 0_u8, 0_u8, 0_u8, 0_u8, // CH#1, enabled
+            2_u8, 20_u8, 0_u8, 0_u8, 0_u8, 0_u8, // FR#1, disabled
+        ];
+
+        let mut endpoints = FixtureEndpoints::new()
+            .with("vpn/v2/logicals", logicals)
+            .with("vpn/v2/status/fixture/binary", status);
+
+        let logicals = get_logicals(&mut endpoints, |_| true).await?;
+
+        // The servers are sorted by name
+        let servers = logicals
+            .logical_servers
+            .iter()
+            .map(|server| (server.name.as_str(), server.status, server.load))
+            .collect::<Vec<_>>();
+        assert_eq!(servers, [("CH#1", 1, 10), ("FR#1", 0, 20)]);
+
+        let logicals =
+            get_logicals(&mut endpoints, |server| server.name == "FR#1")
+                .await?;
+        assert_eq!(logicals.logical_servers.len(), 1);
+
+        assert!(get_logicals(&mut FixtureEndpoints::new(), |_| true)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -3,10 +3,13 @@
 // -----------------------------------------------------------------------------
 
 //! This module provides a ground truth web page for comparing v1 and v2
-//! logicals
+//! logicals, and synthetic data for benchmarking the scoring.
 
-#[cfg(feature = "test_utils_backend")]
+#[cfg(feature = "test_utils_fixtures")]
 pub mod backend;
 
+#[cfg(feature = "test_utils_synthetic")]
+pub mod synthetic;
+
 #[cfg(feature = "test_utils_webview")]
 pub mod webview;
@@ -0,0 +1,143 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+
+//! Reproducible synthetic logicals and status files, to benchmark the
+//! scoring at scale without requesting the back end.
+
+use crate::{
+    CountryCode, Location, Logical, ServerIndex, StatusReference,
+    STATUS_AUTOCONNECTABLE, STATUS_ENABLED, STATUS_VISIBLE,
+};
+use rand::{rngs::StdRng, Rng as _, SeedableRng as _};
+
+// The countries the servers are spread over, with a location inside each.
+const COUNTRIES: [(&[u8; 2], f32, f32); 12] = [
+    (b"CH", 46.8, 8.2),
+    (b"US", 39.8, -98.6),
+    (b"DE", 51.2, 10.4),
+    (b"FR", 46.2, 2.2),
+    (b"GB", 54.0, -2.0),
+    (b"NL", 52.1, 5.3),
+    (b"SE", 62.2, 17.6),
+    (b"JP", 36.2, 138.3),
+    (b"BR", -14.2, -51.9),
+    (b"AU", -25.3, 133.8),
+    (b"CA", 56.1, -106.3),
+    (b"SG", 1.35, 103.8),
+];
+
+// The countries secure core servers are entered from.
+const SECURE_CORE_COUNTRIES: [usize; 2] = [0, 6]; // CH, SE
+
+fn random_location(rng: &mut StdRng, country: usize) -> Location {
+    let (_, latitude, longitude) = COUNTRIES[country];
+    Location {
+        latitude: (latitude + rng.random_range(-3.0..3.0)).clamp(-90.0, 90.0),
+        longitude: (longitude + rng.random_range(-3.0..3.0))
+            .clamp(-180.0, 180.0),
+    }
+}
+
+/// Generates `count` logicals in a fixed set of countries, with the status
+/// index of each logical being its position.
+///
+/// About 10% of the servers are secure core servers, whose entry location is
+/// in another country, and 10% are expensive. The same seed always generates
+/// the same logicals.
+pub fn generate_logicals(count: usize, seed: u64) -> Vec<Logical> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..count)
+        .map(|index| {
+            let country = rng.random_range(0..COUNTRIES.len());
+            let exit_location = random_location(&mut rng, country);
+            let (entry_location, entry_country) = if rng.random_bool(0.1) {
+                let entry_country = SECURE_CORE_COUNTRIES
+                    [rng.random_range(0..SECURE_CORE_COUNTRIES.len())];
+                (
+                    random_location(&mut rng, entry_country),
+                    Some(
+                        CountryCode::try_from(COUNTRIES[entry_country].0)
+                            .expect("Invalid country code"),
+                    ),
+                )
+            } else {
+                (exit_location.clone(), None)
+            };
+
+            let mut logical = Logical {
+                status_reference: StatusReference {
+                    index: ServerIndex(index as u32),
+                    penalty: if rng.random_bool(0.05) { 1.0 } else { 0.0 },
+                    cost: rng.random_bool(0.1) as u8,
+                },
+                entry_location,
+                exit_location,
+                exit_country: CountryCode::try_from(COUNTRIES[country].0)
+                    .expect("Invalid country code"),
+                ..Default::default()
+            };
+            logical.set_entry_country(entry_country);
+            logical
+        })
+        .collect()
+}
+
+/// Generates a version 1 status file containing `count` servers, matching
+/// the logicals returned by `generate_logicals`.
+///
+/// About 5% of the servers are disabled and 5% are hidden. The same seed
+/// always generates the same status file.
+pub fn generate_status_file(count: usize, seed: u64) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut status_file = Vec::with_capacity(4 + count * 6);
+    status_file.extend_from_slice(&[1_u8, 0_u8, 0_u8, 0_u8]);
+
+    for _ in 0..count {
+        let mut status = STATUS_AUTOCONNECTABLE;
+        if !rng.random_bool(0.05) {
+            status |= STATUS_ENABLED;
+        }
+        if !rng.random_bool(0.05) {
+            status |= STATUS_VISIBLE;
+        }
+        let load: u8 = rng.random_range(0..=100);
+        let partial_score: f32 = rng.random_range(0.0..=1.0);
+
+        status_file.push(status);
+        status_file.push(load);
+        status_file.extend_from_slice(&partial_score.to_le_bytes());
+    }
+
+    status_file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_loads, Load, Parser};
+
+    #[test_log::test]
+    fn test_generate() {
+        let logicals = generate_logicals(1000, 7);
+        let status_file = generate_status_file(1000, 7);
+
+        assert_eq!(logicals, generate_logicals(1000, 7));
+        assert_eq!(status_file, generate_status_file(1000, 7));
+        assert_ne!(logicals, generate_logicals(1000, 8));
+
+        assert_eq!(
+            Parser::try_from(&status_file[..]).map(|p| p.len()).ok(),
+            Some(1000)
+        );
+        assert!(crate::validate_references(&logicals, &status_file)
+            .expect("Failed to parse status file")
+            .is_ok());
+
+        let mut loads = vec![Load::default(); logicals.len()];
+        compute_loads(&mut loads, &logicals, &status_file, &None, &None)
+            .expect("Failed to compute loads");
+    }
+}
@@ -0,0 +1,122 @@
+// -----------------------------------------------------------------------------
+// Copyright (c) 2025 Proton AG
+// -----------------------------------------------------------------------------
+use super::status::Parser;
+use super::{Error, Logical, Result, ServerIndex};
+
+/// Checks that every logical references a record present in the status file.
+///
+/// Returns an error if the status file can not be parsed. Otherwise returns
+/// the `(logical_index, status_index)` pairs of logicals whose status index
+/// is past the end of the status file. This usually means the logicals are
+/// newer than the status file, which `compute_loads` silently handles by
+/// using a default status for those servers.
+///
+/// # Arguments
+/// * `logicals` - A slice of `Logical` servers to validate.
+/// * `status_file` - A byte slice representing the binary status file.
+pub fn validate_references(
+    logicals: &[Logical],
+    status_file: &[u8],
+) -> Result<std::result::Result<(), Vec<(usize, ServerIndex)>>> {
+    let statuses = Parser::try_from(status_file)?;
+
+    let dangling = logicals
+        .iter()
+        .enumerate()
+        .filter(|(_, logical)| {
+            logical
+                .status_reference
+                .index
+                .to_usize()
+                .is_none_or(|index| index >= statuses.len())
+        })
+        .map(|(i, logical)| (i, logical.status_reference.index))
+        .collect::<Vec<_>>();
+
+    if dangling.is_empty() {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(dangling))
+    }
+}
+
+// Returns an error for the first pair of logicals sharing a status index.
+pub(crate) fn check_duplicate_status_indices(
+    logicals: &[Logical],
+) -> Result<()> {
+    let mut seen = std::collections::HashMap::with_capacity(logicals.len());
+
+    for (second, logical) in logicals.iter().enumerate() {
+        let index = logical.status_reference.index;
+        if let Some(first) = seen.insert(index, second) {
+            return Err(Error::DuplicateStatusIndex {
+                index: index.into(),
+                first: first as u64,
+                second: second as u64,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{make_logical, make_status_file};
+
+    type AnyResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test_log::test]
+    fn test_validate_references() -> AnyResult {
+        let status_file = make_status_file(&[(3, 0, 0.0), (3, 0, 0.0)]);
+
+        assert_eq!(
+            validate_references(
+                &[make_logical(1), make_logical(0)],
+                &status_file
+            )?,
+            Ok(())
+        );
+
+        assert_eq!(
+            validate_references(
+                &[make_logical(0), make_logical(2), make_logical(7)],
+                &status_file
+            )?,
+            Err(vec![(1, ServerIndex(2)), (2, ServerIndex(7))])
+        );
+
+        assert!(validate_references(&[make_logical(0)], &[8_u8]).is_err());
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_check_duplicate_status_indices() {
+        assert!(check_duplicate_status_indices(&[
+            make_logical(1),
+            make_logical(0),
+        ])
+        .is_ok());
+
+        let error = check_duplicate_status_indices(&[
+            make_logical(3),
+            make_logical(1),
+            make_logical(2),
+            make_logical(1),
+            make_logical(3),
+        ])
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::DuplicateStatusIndex {
+                index: 1,
+                first: 1,
+                second: 3
+            }
+        ));
+    }
+}
@@ -0,0 +1,273 @@
+#![cfg(feature = "cffi")]
+
+use std::ffi::{c_char, CStr};
+use std::ptr::{null, null_mut};
+
+use proton_vpn_binary_status::bindings_cffi::{
+    compute_loads_cffi, compute_loads_cffi_v3, ffi_struct_version,
+    free_c_string, status_file_record_count, LoadV1, FFI_STRUCT_VERSION,
+};
+use proton_vpn_binary_status::{
+    CountryCode, Load, Location, Logical, OptionalCountryCode, ServerIndex,
+    StatusReference,
+};
+
+const STATUS_FILE: [u8; 16] = [
+    1_u8, 0_u8, 0_u8, 0_u8, // Version
+    3_u8, 42_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 0
+    3_u8, 57_u8, 0_u8, 0_u8, 0_u8, 0_u8, // Server 1
+];
+
+fn logicals() -> Vec<Logical> {
+    (0..2)
+        .map(|index| Logical {
+            status_reference: StatusReference {
+                index: ServerIndex(index),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .collect()
+}
+
+// Calls `compute_loads_cffi` and returns its result code and error message,
+// freeing the error string with `free_c_string`.
+fn compute(
+    logicals: &[Logical],
+    status_file: &[u8],
+    user_country: Option<&[u8; 2]>,
+    loads: &mut [LoadV1],
+) -> (i32, Option<String>) {
+    let user_location = Location {
+        latitude: 46.2044,
+        longitude: 6.1432,
+    };
+    let mut error: *mut c_char = null_mut();
+
+    let result = compute_loads_cffi(
+        logicals.as_ptr(),
+        logicals.len(),
+        status_file.as_ptr(),
+        status_file.len(),
+        &user_location,
+        user_country.map_or(null(), |country| country as *const _),
+        loads.as_mut_ptr(),
+        &mut error,
+    );
+
+    (result, take_error(error))
+}
+
+fn take_error(error: *mut c_char) -> Option<String> {
+    if error.is_null() {
+        return None;
+    }
+
+    // nosem: rust.lang.security.unsafe-usage.unsafe-usage
+    let message = unsafe { CStr::from_ptr(error) }
+        .to_string_lossy()
+        .into_owned();
+    free_c_string(error);
+
+    Some(message)
+}
+
+#[test_log::test]
+fn test_compute_loads_cffi() {
+    let logicals = logicals();
+    let mut loads = vec![LoadV1::default(); logicals.len()];
+
+    let (result, error) =
+        compute(&logicals, &STATUS_FILE, Some(b"ch"), &mut loads);
+
+    assert_eq!(result, 0);
+    assert_eq!(error, None);
+    assert_eq!(loads[0].load, 42);
+    assert_eq!(loads[1].load, 57);
+    assert!(loads.iter().all(|load| load.is_enabled));
+
+    // The user country is optional
+    let (result, error) = compute(&logicals, &STATUS_FILE, None, &mut loads);
+    assert_eq!((result, error), (0, None));
+}
+
+#[test_log::test]
+fn test_compute_loads_cffi_v3() {
+    let logicals = logicals();
+    let user_location = Location {
+        latitude: 46.2044,
+        longitude: 6.1432,
+    };
+    let mut loads = vec![Load::default(); logicals.len()];
+    let mut error: *mut c_char = null_mut();
+
+    let result = compute_loads_cffi_v3(
+        logicals.as_ptr(),
+        logicals.len(),
+        STATUS_FILE.as_ptr(),
+        STATUS_FILE.len(),
+        &user_location,
+        null(),
+        loads.as_mut_ptr(),
+        &mut error,
+    );
+    assert_eq!((result, take_error(error)), (0, None));
+    assert_eq!(loads[0].load, 42);
+    assert_eq!(loads[1].load, 57);
+    assert!(loads.iter().all(|load| load.is_enabled));
+
+    // The version 1 loads are the same, without the newer fields
+    let mut loads_v1 = vec![LoadV1::default(); logicals.len()];
+    assert_eq!(
+        compute(&logicals, &STATUS_FILE, None, &mut loads_v1),
+        (0, None)
+    );
+    for (load, load_v1) in std::iter::zip(&loads, &loads_v1) {
+        assert_eq!(load_v1.load, load.load);
+        assert_eq!(load_v1.is_enabled, load.is_enabled);
+        // The scores only differ by the jitter
+        assert!((load_v1.score - load.score.value()).abs() <= 0.01);
+    }
+
+    let result = compute_loads_cffi_v3(
+        logicals.as_ptr(),
+        logicals.len(),
+        STATUS_FILE.as_ptr(),
+        STATUS_FILE.len(),
+        null(),
+        null(),
+        null_mut(),
+        &mut error,
+    );
+    assert_eq!(result, -1);
+    assert_eq!(
+        take_error(error).as_deref(),
+        Some("null pointer for required parameter")
+    );
+}
+
+#[test_log::test]
+fn test_compute_loads_cffi_null_pointers() {
+    let logicals = logicals();
+    let mut loads = vec![LoadV1::default(); logicals.len()];
+    let mut error: *mut c_char = null_mut();
+
+    let result = compute_loads_cffi(
+        null(),
+        logicals.len(),
+        STATUS_FILE.as_ptr(),
+        STATUS_FILE.len(),
+        null(),
+        null(),
+        loads.as_mut_ptr(),
+        &mut error,
+    );
+    assert_eq!(result, -1);
+    assert_eq!(
+        take_error(error).as_deref(),
+        Some("null pointer for required parameter")
+    );
+
+    // Without an error out-parameter, only the result code is returned
+    let result = compute_loads_cffi(
+        logicals.as_ptr(),
+        logicals.len(),
+        null(),
+        STATUS_FILE.len(),
+        null(),
+        null(),
+        loads.as_mut_ptr(),
+        null_mut(),
+    );
+    assert_eq!(result, -1);
+
+    let result = compute_loads_cffi(
+        logicals.as_ptr(),
+        logicals.len(),
+        STATUS_FILE.as_ptr(),
+        STATUS_FILE.len(),
+        null(),
+        null(),
+        null_mut(),
+        null_mut(),
+    );
+    assert_eq!(result, -1);
+
+    // Freeing a null error string is a no-op
+    free_c_string(null_mut());
+}
+
+#[test_log::test]
+fn test_compute_loads_cffi_invalid_country() {
+    let logicals = logicals();
+    let mut loads = vec![LoadV1::default(); logicals.len()];
+
+    let (result, error) =
+        compute(&logicals, &STATUS_FILE, Some(b"\xc3\xa9"), &mut loads);
+
+    assert_eq!(result, -2);
+    assert_eq!(error.as_deref(), Some("Country code must be ascii letters"));
+}
+
+#[test_log::test]
+fn test_compute_loads_cffi_invalid_status_file() {
+    let logicals = logicals();
+    let mut loads = vec![LoadV1::default(); logicals.len()];
+
+    // The last record is truncated
+    let status_file = &STATUS_FILE[..STATUS_FILE.len() - 2];
+    let (result, error) = compute(&logicals, status_file, None, &mut loads);
+
+    assert_eq!(result, -3);
+    assert!(error.is_some_and(|error| error.contains("status file")));
+}
+
+#[test_log::test]
+fn test_status_file_record_count() {
+    let mut error: *mut c_char = null_mut();
+
+    let count = status_file_record_count(
+        STATUS_FILE.as_ptr(),
+        STATUS_FILE.len(),
+        &mut error,
+    );
+    assert_eq!(count, 2);
+    assert_eq!(take_error(error), None);
+
+    // The last record is truncated
+    let count = status_file_record_count(
+        STATUS_FILE.as_ptr(),
+        STATUS_FILE.len() - 2,
+        &mut error,
+    );
+    assert_eq!(count, -3);
+    assert!(
+        take_error(error).is_some_and(|error| error.contains("status file"))
+    );
+
+    let count = status_file_record_count(null(), 0, &mut error);
+    assert_eq!(count, -1);
+    assert_eq!(
+        take_error(error).as_deref(),
+        Some("null pointer for required parameter")
+    );
+}
+
+#[test_log::test]
+fn test_entry_country() {
+    assert_eq!(ffi_struct_version(), FFI_STRUCT_VERSION);
+
+    // As filled in by a C caller, zeroed for a server without entry country
+    let mut logicals = logicals();
+    logicals[1].entry_country = OptionalCountryCode::from_bytes(*b"IS");
+    assert_eq!(logicals[0].entry_country(), None);
+    assert_eq!(
+        logicals[1].entry_country(),
+        Some(CountryCode::try_from(b"IS").expect("Invalid country code"))
+    );
+
+    let mut loads = vec![LoadV1::default(); logicals.len()];
+    let (result, error) =
+        compute(&logicals, &STATUS_FILE, Some(b"ch"), &mut loads);
+    assert_eq!((result, error), (0, None));
+}
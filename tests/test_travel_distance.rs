@@ -0,0 +1,66 @@
+use proton_vpn_binary_status::{
+    compute_travel_distance, compute_travel_distance_legacy,
+    compute_travel_distance_modern, legacy_enabled, Location,
+};
+
+// A secure core server, entering in Iceland and exiting in France.
+const ENTRY: Location = Location {
+    latitude: 64.1466, // Reykjavik
+    longitude: -21.9426,
+};
+const EXIT: Location = Location {
+    latitude: 48.8566, // Paris
+    longitude: 2.3522,
+};
+
+fn assert_near(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-6, "{a} != {b}");
+}
+
+#[test_log::test]
+fn test_travel_distance_formulas() {
+    let internal = compute_travel_distance_modern(&EXIT, &ENTRY, &ENTRY);
+    assert!(internal > 0.0);
+
+    // From the entry, the modern formula only has the internal leg, while
+    // the legacy one goes to the exit first.
+    assert_near(
+        compute_travel_distance_legacy(&EXIT, &ENTRY, &ENTRY),
+        2.0 * internal,
+    );
+
+    // From the exit, it is the other way around.
+    assert_near(
+        compute_travel_distance_legacy(&EXIT, &ENTRY, &EXIT),
+        internal,
+    );
+    assert_near(
+        compute_travel_distance_modern(&EXIT, &ENTRY, &EXIT),
+        2.0 * internal,
+    );
+
+    // Without an internal leg, both formulas agree.
+    let client = Location {
+        latitude: 51.5074, // London
+        longitude: -0.1278,
+    };
+    assert_near(
+        compute_travel_distance_legacy(&EXIT, &EXIT, &client),
+        compute_travel_distance_modern(&EXIT, &EXIT, &client),
+    );
+}
+
+#[test_log::test]
+fn test_travel_distance_default_formula() {
+    let client = Location {
+        latitude: 51.5074, // London
+        longitude: -0.1278,
+    };
+
+    let expected = if legacy_enabled() {
+        compute_travel_distance_legacy(&EXIT, &ENTRY, &client)
+    } else {
+        compute_travel_distance_modern(&EXIT, &ENTRY, &client)
+    };
+    assert_eq!(compute_travel_distance(&EXIT, &ENTRY, &client), expected);
+}